@@ -1,23 +1,116 @@
 use std::fs::File;
+use std::io::Write;
+use std::path::Path;
 
-use crate::common::{ChallengeTime, Game};
+use crate::common::{format_timestamp, ChallengeTime, Endianness, Game};
+use crate::converter::DSCConverter;
 use crate::dsc::DSCVM;
 use crate::error::{ApplicationError, ApplicationResult};
+use crate::input::{open_input, open_output, stdin_contents, InputSpec, STDIO_PATH};
 use crate::logger::Logger;
 use crate::merger::DSCMerger;
+use crate::opcodes::{clear_opcode_definitions, load_opcode_definitions, Command, Opcode};
+use crate::retime::RetimeEdit;
 use crate::subtitle::SubtitleKind;
 
+/// Everything that decides what a merge does. Apart from the inputs, every
+/// option has a default, so only the ones that matter need to be set:
+///
+/// ```no_run
+/// use dsc_merger::{Application, ApplicationOptions, SimpleLogger};
+///
+/// let options = ApplicationOptions {
+///     dsc_inputs: vec!["chart.dsc".to_string(), "camera.dsc".to_string()],
+///     output: "merged.dsc".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let mut logger = SimpleLogger::new();
+/// Application::new(options, &mut logger).run()?;
+/// # Ok::<(), dsc_merger::ApplicationError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApplicationOptions {
+    /// Encoded scripts. Paths may have `@` modifiers, see [`InputSpec`].
+    pub dsc_inputs: Vec<String>,
+    pub plaintext_inputs: Vec<String>,
+    pub subtitle_inputs: Vec<String>,
+    /// Inputs to remove the targets from.
+    pub remove_targets_inputs: Vec<String>,
+    /// Files with extra opcode definitions.
+    pub opcode_tables: Vec<String>,
+    pub pad_missing_args: bool,
+    /// Time in ticks to move every input by.
+    pub offset: i32,
+    pub retime_edits: Vec<RetimeEdit>,
+    /// Play the inputs one after another instead of at the same time.
+    pub medley: bool,
+    /// The positions the encoded and then the plaintext inputs were given
+    /// at, which sets their order in a medley. If empty, the encoded inputs
+    /// come first.
+    pub input_order: Vec<usize>,
+    pub output: String,
+    /// The game of the inputs, detected from them if not given.
+    pub game: Option<Game>,
+    pub convert_to: Option<Game>,
+    pub endianness: Option<Endianness>,
+    pub pv_id: u16,
+    pub english_lyrics: bool,
+    pub max_lyric_length: u16,
+    pub dump: bool,
+    pub dump_output: Option<String>,
+    pub verbose: bool,
+    pub challenge_time: Option<ChallengeTime>,
+}
+
+impl Default for ApplicationOptions {
+    fn default() -> Self {
+        Self {
+            dsc_inputs: Vec::new(),
+            plaintext_inputs: Vec::new(),
+            subtitle_inputs: Vec::new(),
+            remove_targets_inputs: Vec::new(),
+            opcode_tables: Vec::new(),
+            pad_missing_args: false,
+            offset: 0,
+            retime_edits: Vec::new(),
+            medley: false,
+            input_order: Vec::new(),
+            output: "output.dsc".to_string(),
+            game: None,
+            convert_to: None,
+            endianness: None,
+            pv_id: 0,
+            english_lyrics: false,
+            max_lyric_length: 75,
+            dump: false,
+            dump_output: None,
+            verbose: false,
+            challenge_time: None,
+        }
+    }
+}
+
 pub struct Application<'a> {
-    dsc_inputs: Vec<String>,
-    plaintext_inputs: Vec<String>,
-    subtitle_inputs: Vec<String>,
+    dsc_inputs: Vec<InputSpec>,
+    plaintext_inputs: Vec<InputSpec>,
+    subtitle_inputs: Vec<InputSpec>,
     remove_targets_inputs: Vec<String>,
+    opcode_tables: Vec<String>,
+    pad_missing_args: bool,
+    offset: i32,
+    retime_edits: Vec<RetimeEdit>,
+    medley: bool,
+    input_order: Vec<usize>,
     output: String,
-    game: Game,
+    game: Option<Game>,
+    convert_to: Option<Game>,
+    endianness: Option<Endianness>,
     pv_id: u16,
     english_lyrics: bool,
     max_lyric_length: u16,
     dump: bool,
+    dump_output: Option<String>,
     verbose: bool,
     challenge_time: Option<ChallengeTime>,
 
@@ -25,194 +118,577 @@ pub struct Application<'a> {
 }
 
 impl<'a> Application<'a> {
-    pub fn new(
-        dsc_inputs: Vec<String>,
-        plaintext_inputs: Vec<String>,
-        subtitle_inputs: Vec<String>,
-        remove_targets_inputs: Vec<String>,
-        output: String,
-        game: Game,
-        pv_id: u16,
-        english_lyrics: bool,
-        max_lyric_length: u16,
-        dump: bool,
-        verbose: bool,
-        challenge_time: Option<ChallengeTime>,
-        logger: &'a mut dyn Logger,
-    ) -> Self {
+    pub fn new(options: ApplicationOptions, logger: &'a mut dyn Logger) -> Self {
+        let parse_inputs =
+            |inputs: Vec<String>| inputs.iter().map(|spec| InputSpec::parse(spec)).collect();
+
         Self {
-            dsc_inputs,
-            plaintext_inputs,
-            subtitle_inputs,
-            remove_targets_inputs,
-            output,
-            game,
-            pv_id,
-            english_lyrics,
-            max_lyric_length,
-            dump,
-            verbose,
-            challenge_time,
+            dsc_inputs: parse_inputs(options.dsc_inputs),
+            plaintext_inputs: parse_inputs(options.plaintext_inputs),
+            subtitle_inputs: parse_inputs(options.subtitle_inputs),
+            remove_targets_inputs: options
+                .remove_targets_inputs
+                .iter()
+                .map(|spec| InputSpec::parse(spec).path)
+                .collect(),
+            opcode_tables: options.opcode_tables,
+            pad_missing_args: options.pad_missing_args,
+            offset: options.offset,
+            retime_edits: options.retime_edits,
+            medley: options.medley,
+            input_order: options.input_order,
+            output: options.output,
+            game: options.game,
+            convert_to: options.convert_to,
+            endianness: options.endianness,
+            pv_id: options.pv_id,
+            english_lyrics: options.english_lyrics,
+            max_lyric_length: options.max_lyric_length,
+            dump: options.dump,
+            dump_output: options.dump_output,
+            verbose: options.verbose,
+            challenge_time: options.challenge_time,
             logger,
         }
     }
 
-    fn handle_file(&self, filename: &str) -> ApplicationResult<DSCVM> {
-        let file = File::open(filename);
-        let remove_targets = self.remove_targets_inputs.contains(&filename.to_string());
+    /// Works out which game each encoded input was made for. Inputs with an
+    /// explicit `@game` use that, then `-g` is used, and otherwise the game
+    /// is detected from the header. Detected games have to agree with each
+    /// other, which also settles inputs that read the same as F2nd and X
+    /// scripts. Also returns the game that plaintext inputs and the output
+    /// default to.
+    fn detect_games(&mut self) -> ApplicationResult<(Vec<Game>, Game)> {
+        let mut candidates = Vec::new();
+
+        for input in &self.dsc_inputs {
+            if let Some(game) = input.game.or(self.game) {
+                candidates.push(vec![game]);
+                continue;
+            }
 
-        match file {
-            Ok(mut file) => {
-                let dsc_vm = DSCVM::load(self.game, &mut file, remove_targets)?;
+            let mut file = open_input(&input.path)?;
+            let possible = DSCVM::possible_games(&mut file)?;
 
-                Ok(dsc_vm)
+            if possible.is_empty() {
+                return Err(ApplicationError::UndetectableGame(input.path.to_owned()));
             }
-            Err(_) => Err(ApplicationError::FileNotFound(filename.to_owned())),
+
+            candidates.push(possible);
         }
-    }
 
-    fn handle_plaintext_file(&self, filename: &str) -> ApplicationResult<DSCVM> {
-        let file = std::fs::File::open(filename);
-        let remove_targets = self.remove_targets_inputs.contains(&filename.to_string());
+        let mut detected: Option<Game> = None;
+
+        for (input, possible) in self.dsc_inputs.iter().zip(&candidates) {
+            if input.game.or(self.game).is_some() || possible.len() > 1 {
+                continue;
+            }
 
-        match file {
-            Ok(mut file) => {
-                let dsc_vm = DSCVM::load_plaintext(self.game, &mut file, remove_targets)?;
+            let game = possible[0];
 
-                Ok(dsc_vm)
+            match detected {
+                Some(expected) if expected != game => {
+                    return Err(ApplicationError::GameMismatch(
+                        input.path.to_owned(),
+                        expected,
+                        game,
+                    ));
+                }
+                _ => detected = Some(game),
             }
-            Err(_) => Err(ApplicationError::FileNotFound(filename.to_owned())),
         }
+
+        let mut games = Vec::new();
+
+        for (input, possible) in self.dsc_inputs.iter().zip(candidates) {
+            let game = match possible[..] {
+                [game] => game,
+                _ => match detected {
+                    Some(game) if possible.contains(&game) => game,
+                    Some(expected) => {
+                        return Err(ApplicationError::GameMismatch(
+                            input.path.to_owned(),
+                            expected,
+                            possible[0],
+                        ))
+                    }
+                    None => {
+                        let game = DSCVM::preferred_game(&possible).unwrap_or(Game::F2nd);
+
+                        self.logger.log(format!(
+                            "Warning: \"{}\" reads as a script for Project Diva {} alike; assuming {} (use -g to choose).",
+                            input.path,
+                            possible
+                                .iter()
+                                .map(|game| game.to_string())
+                                .collect::<Vec<String>>()
+                                .join(" and "),
+                            game
+                        ));
+
+                        game
+                    }
+                },
+            };
+
+            if self.verbose && input.game.or(self.game).is_none() {
+                self.logger.log(format!(
+                    "Detected Project Diva {} script: \"{}\".",
+                    game, input.path
+                ));
+            }
+
+            games.push(game);
+        }
+
+        let primary = self
+            .game
+            .or(detected)
+            .or(games.first().copied())
+            .unwrap_or(Game::FutureTone);
+
+        Ok((games, primary))
     }
 
-    fn handle_subtitle_file(&mut self, filename: &str) -> ApplicationResult<DSCVM> {
-        let extension = filename.split('.').last().unwrap_or("srt");
-        let kind = SubtitleKind::from_extension(extension);
+    /// Applies the offset and section of an input to the script loaded
+    /// from it.
+    fn apply_modifiers(&self, input: &InputSpec, mut dsc_vm: DSCVM) -> DSCVM {
+        dsc_vm.offset = self
+            .offset
+            .saturating_add(input.offset)
+            .saturating_add(input.start.unwrap_or(0));
+        dsc_vm.filters = input.filters.clone();
+
+        match input.section {
+            Some(section) => section.apply(&dsc_vm),
+            None => dsc_vm,
+        }
+    }
 
-        if kind.is_none() {
-            return Err(ApplicationError::InvalidSubtitleFile);
+    /// Makes sure that the opcodes named by the filters of an input exist in
+    /// its game, so that a typo doesn't quietly filter out everything.
+    fn check_filters(game: Game, input: &InputSpec) -> ApplicationResult {
+        for selector in input.filters.iter().flat_map(|filter| &filter.selectors) {
+            if let Some(name) = selector.opcode_name() {
+                Command::get_opcode_meta_from_name(game, name.into_owned())?;
+            }
         }
 
-        let file = std::fs::File::open(filename);
+        Ok(())
+    }
 
-        match file {
-            Ok(mut file) => {
-                let dsc_vm = DSCVM::load_subtitle(
-                    &mut file,
-                    kind.unwrap(),
-                    self.pv_id,
-                    self.english_lyrics,
-                    self.max_lyric_length,
-                    self.logger,
-                )?;
+    fn handle_file(&self, game: Game, input: &InputSpec) -> ApplicationResult<DSCVM> {
+        Self::check_filters(game, input)?;
+
+        let mut file = open_input(&input.path)?;
+        let remove_targets = self.remove_targets_inputs.contains(&input.path);
+
+        let dsc_vm = DSCVM::load(game, &mut file, remove_targets)?;
+
+        Ok(self.apply_modifiers(input, dsc_vm))
+    }
+
+    fn handle_plaintext_file(&self, game: Game, input: &InputSpec) -> ApplicationResult<DSCVM> {
+        Self::check_filters(game, input)?;
+
+        let remove_targets = self.remove_targets_inputs.contains(&input.path);
+
+        let dsc_vm = DSCVM::load_text_file(
+            game,
+            Path::new(&input.path),
+            remove_targets,
+            self.pad_missing_args,
+        )?;
 
-                Ok(dsc_vm)
+        Ok(self.apply_modifiers(input, dsc_vm))
+    }
+
+    fn handle_subtitle_file(&mut self, input: &InputSpec) -> ApplicationResult<DSCVM> {
+        let kind = if input.path == STDIO_PATH {
+            Some(SubtitleKind::from_contents(stdin_contents()?))
+        } else {
+            let extension = input.path.split('.').next_back().unwrap_or("srt");
+            SubtitleKind::from_extension(extension)
+        };
+
+        let kind = match kind {
+            Some(kind) => kind,
+            None => return Err(ApplicationError::InvalidSubtitleFile),
+        };
+
+        let mut file = open_input(&input.path)?;
+
+        let dsc_vm = DSCVM::load_subtitle(
+            &mut file,
+            kind,
+            self.pv_id,
+            self.english_lyrics,
+            self.max_lyric_length,
+            self.logger,
+        )?;
+
+        Ok(self.apply_modifiers(input, dsc_vm))
+    }
+
+    /// Places the scripts of a medley one after another, in the order they
+    /// were given in. Scripts without a start time of their own begin where
+    /// the previous one ends (at its `PV_END`, or its last command
+    /// otherwise). Only the first script keeps its `MUSIC_PLAY`, and none
+    /// keeps its `PV_END`; returns the time the medley ends at instead.
+    fn chain_medley(&mut self, scripts: &mut Vec<(InputSpec, DSCVM)>) -> i32 {
+        if self.input_order.len() == scripts.len() {
+            let mut ordered = self
+                .input_order
+                .iter()
+                .zip(std::mem::take(scripts))
+                .collect::<Vec<_>>();
+            ordered.sort_by_key(|(&position, _)| position);
+            *scripts = ordered.into_iter().map(|(_, script)| script).collect();
+        }
+
+        let mut chain_time = 0;
+
+        for (idx, (input, dsc_vm)) in scripts.iter_mut().enumerate() {
+            if input.start.is_none() {
+                dsc_vm.offset = dsc_vm.offset.saturating_add(chain_time);
+            }
+
+            let start = dsc_vm.offset.saturating_sub(self.offset);
+
+            if self.verbose {
+                self.logger.log(format!(
+                    "Medley: \"{}\" starts at {}.",
+                    input.path,
+                    format_timestamp(start)
+                ));
+            }
+
+            chain_time = start.saturating_add(dsc_vm.end_time());
+
+            // The merged script gets a single END of its own.
+            let mut stripped = vec![Opcode::END, Opcode::PV_END];
+
+            if idx > 0 {
+                stripped.push(Opcode::MUSIC_PLAY);
             }
-            Err(_) => Err(ApplicationError::FileNotFound(filename.to_owned())),
+
+            dsc_vm.remove_opcodes(&stripped);
         }
+
+        chain_time.saturating_add(self.offset)
+    }
+
+    /// Standard input can only be read once, and standard output can only
+    /// hold one of the output and the dump.
+    fn check_stdio(&self) -> ApplicationResult {
+        let stdin_inputs = self
+            .dsc_inputs
+            .iter()
+            .chain(&self.plaintext_inputs)
+            .chain(&self.subtitle_inputs)
+            .filter(|input| input.path == STDIO_PATH)
+            .count();
+
+        if stdin_inputs > 1 {
+            return Err(ApplicationError::StdinReused);
+        }
+
+        let dump_to_stdout = match &self.dump_output {
+            Some(dump_output) => dump_output == STDIO_PATH,
+            None => self.dump,
+        };
+
+        if self.output == STDIO_PATH && dump_to_stdout {
+            return Err(ApplicationError::StdoutReused);
+        }
+
+        Ok(())
     }
 
     pub fn run(&mut self) -> ApplicationResult {
+        if self.dsc_inputs.is_empty()
+            && self.plaintext_inputs.is_empty()
+            && self.subtitle_inputs.is_empty()
+        {
+            return Err(ApplicationError::NoInputFiles);
+        }
+
+        self.check_stdio()?;
+
+        // Each run only uses its own opcode tables.
+        clear_opcode_definitions();
+
+        for filename in &self.opcode_tables {
+            if self.verbose {
+                self.logger
+                    .log(format!("Loading opcode definitions: \"{}\"...", filename));
+            }
+
+            match File::open(filename) {
+                Ok(mut file) => load_opcode_definitions(&mut file)?,
+                Err(_) => return Err(ApplicationError::FileNotFound(filename.to_owned())),
+            }
+        }
+
+        let (games, game) = self.detect_games()?;
+        let output_game = self.convert_to.unwrap_or(game);
+
         if self.verbose {
             self.logger.log(format!(
                 "Merging charts for target game: Project Diva {}.",
-                self.game.to_string()
+                output_game
             ));
         }
 
-        if self.dsc_inputs.len() == 0
-            && self.plaintext_inputs.len() == 0
-            && self.subtitle_inputs.len() == 0
-        {
-            return Err(ApplicationError::NoInputFiles);
-        }
-
         let mut merger = DSCMerger::new();
+        let mut converter = DSCConverter::new(output_game);
+        let mut input_endianness: Option<Endianness> = None;
+        let mut scripts = Vec::new();
 
-        for filename in &self.dsc_inputs {
+        for (input, &input_game) in self.dsc_inputs.iter().zip(&games) {
             if self.verbose {
                 self.logger
-                    .log(format!("Loading DSC file: \"{}\"...", filename));
+                    .log(format!("Loading DSC file: \"{}\"...", input.path));
+
+                if input_game != output_game {
+                    self.logger.log(format!(
+                        "Converting \"{}\" from Project Diva {} to Project Diva {}.",
+                        input.path, input_game, output_game
+                    ));
+                }
             }
 
-            let dsc_vm = self.handle_file(&filename);
+            let dsc_vm = self.handle_file(input_game, input);
 
             match dsc_vm {
-                Ok(dsc_vm) => merger.add_dsc(dsc_vm),
+                Ok(dsc_vm) => {
+                    input_endianness.get_or_insert(dsc_vm.endianness);
+                    scripts.push((input.clone(), converter.convert(input_game, dsc_vm)));
+                }
                 Err(e) => {
                     return Err(e);
                 }
             }
         }
 
-        for filename in &self.plaintext_inputs {
+        for input in &self.plaintext_inputs {
+            let input_game = input.game.unwrap_or(game);
+
             if self.verbose {
                 self.logger.log(format!(
                     "Loading plaintext/dumped DSC file: \"{}\"...",
-                    filename
+                    input.path
                 ));
             }
 
-            let dsc_vm = self.handle_plaintext_file(&filename);
+            let dsc_vm = self.handle_plaintext_file(input_game, input);
 
             match dsc_vm {
-                Ok(dsc_vm) => merger.add_dsc(dsc_vm),
+                Ok(dsc_vm) => scripts.push((input.clone(), converter.convert(input_game, dsc_vm))),
                 Err(e) => {
                     return Err(e);
                 }
             }
         }
 
-        for filename in self.subtitle_inputs.clone() {
+        let medley_end = if self.medley {
+            Some(self.chain_medley(&mut scripts))
+        } else {
+            None
+        };
+
+        for (_, dsc_vm) in scripts {
+            merger.add_dsc(dsc_vm);
+        }
+
+        // Added after the inputs so that no filter can drop it.
+        if let Some(end) = medley_end {
+            if let Some(meta) = Command::get_opcode_meta_from_opcode(output_game, Opcode::PV_END) {
+                merger.add_command(end, Command::new(meta, Vec::new()));
+            }
+        }
+
+        for input in self.subtitle_inputs.clone() {
             if self.verbose {
                 self.logger
-                    .log(format!("Loading subtitle file: \"{}\"...", filename));
+                    .log(format!("Loading subtitle file: \"{}\"...", input.path));
             }
 
-            let dsc_vm = self.handle_subtitle_file(&filename);
+            let dsc_vm = self.handle_subtitle_file(&input);
 
             match dsc_vm {
-                Ok(dsc_vm) => merger.add_dsc(dsc_vm),
+                Ok(dsc_vm) => merger.add_dsc(converter.convert(output_game, dsc_vm)),
                 Err(e) => {
                     return Err(e);
                 }
             }
         }
 
-        match self.challenge_time {
-            Some(challenge_time) => {
-                if self.verbose {
-                    self.logger
-                        .log(format!("Adding challenge time: {}", challenge_time));
-                    merger.add_challenge_time(challenge_time);
+        for (opcode, count) in &converter.dropped {
+            self.logger.log(format!(
+                "Warning: Dropped {} {} command(s) that Project Diva {} does not support.",
+                count, opcode, output_game
+            ));
+        }
+
+        for (opcode, count) in &converter.resized {
+            self.logger.log(format!(
+                "Warning: Adjusted the parameters of {} {} command(s) for Project Diva {}.",
+                count, opcode, output_game
+            ));
+        }
+
+        for (field, count) in &converter.lost_fields {
+            self.logger.log(format!(
+                "Warning: Lost the {} of {} TARGET command(s), which Project Diva {} does not have.",
+                field, count, output_game
+            ));
+        }
+
+        for (field, count) in &converter.invented_fields {
+            self.logger.log(format!(
+                "Warning: Set the {} of {} TARGET command(s) to 0, as the input does not have it.",
+                field, count
+            ));
+        }
+
+        if merger.moved_to_start > 0 {
+            self.logger.log(format!(
+                "Warning: Moved {} command(s) that the offset put before the start of the script to the start.",
+                merger.moved_to_start
+            ));
+        }
+
+        if merger.dropped_targets > 0 {
+            self.logger.log(format!(
+                "Warning: Dropped {} target command(s) that the offset put before the start of the script.",
+                merger.dropped_targets
+            ));
+        }
+
+        if !self.retime_edits.is_empty() {
+            if self.verbose {
+                for edit in &self.retime_edits {
+                    self.logger.log(format!("Retiming: {}", edit));
                 }
             }
-            None => {}
+
+            let removed = merger.retime(&self.retime_edits);
+
+            if self.verbose {
+                self.logger.log(format!(
+                    "Removed {} command(s) in the removed time ranges.",
+                    removed
+                ));
+            }
+        }
+
+        if let Some(challenge_time) = self.challenge_time {
+            if self.verbose {
+                self.logger
+                    .log(format!("Adding challenge time: {}", challenge_time));
+            }
+
+            merger.add_challenge_time(challenge_time);
         }
 
         if self.verbose {
             self.logger.log("Merging DSC commands...".to_string())
         }
 
-        let new_dsc = merger.to_dsc();
+        let mut new_dsc = merger.to_dsc();
 
-        if self.dump {
-            println!("{}", new_dsc.dump());
+        // Unless told otherwise, keep the byte order of the first encoded input.
+        new_dsc.endianness = self.endianness.or(input_endianness).unwrap_or_default();
+
+        match &self.dump_output {
+            Some(dump_output) => {
+                let mut writer = open_output(dump_output)?;
+                writer.write_all(new_dsc.dump().as_bytes())?;
+            }
+            None if self.dump => println!("{}", new_dsc.dump()),
+            None => {}
         }
 
         if self.verbose {
             self.logger.log(format!(
-                "Writing merged DSC to file: \"{}\"...",
-                self.output
+                "Writing merged {} DSC to file: \"{}\"...",
+                new_dsc.endianness, self.output
             ));
         }
 
-        let output_file = File::create(&self.output);
+        let mut output_file = open_output(&self.output)?;
+        new_dsc.write(output_game, &mut output_file)
+    }
+}
 
-        if let Ok(mut output_file) = output_file {
-            new_dsc.write(self.game, &mut output_file)
-        } else {
-            Err(ApplicationError::WriteFileFailed)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::simple_logger::SimpleLogger;
+    use crate::opcodes::CUSTOM_DEFINITIONS_LOCK;
+
+    #[test]
+    fn medley() {
+        // Runs clear the custom opcode definitions other tests rely on.
+        let _lock = CUSTOM_DEFINITIONS_LOCK.lock().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("dsc-merger-medley-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = dir.join("first.txt");
+        let second = dir.join("second.txt");
+        let output = dir.join("output.dsc");
+
+        std::fs::write(
+            &first,
+            "TIME(0); MUSIC_PLAY(); TIME(100000); LYRIC(1, -1); TIME(300000); PV_END(); END();",
+        )
+        .unwrap();
+
+        // Without a PV_END of its own, it ends with its last command.
+        std::fs::write(
+            &second,
+            "TIME(0); MUSIC_PLAY(); TIME(50000); LYRIC(2, -1); TIME(200000); CHANGE_FIELD(1);",
+        )
+        .unwrap();
+
+        let options = ApplicationOptions {
+            plaintext_inputs: vec![second.display().to_string(), first.display().to_string()],
+            // As in `-p first.txt ... -p second.txt`.
+            input_order: vec![5, 2],
+            medley: true,
+            game: Some(Game::FutureTone),
+            output: output.display().to_string(),
+            ..Default::default()
+        };
+
+        let mut logger = SimpleLogger::new();
+        Application::new(options, &mut logger).run().unwrap();
+
+        let merged = DSCVM::load(Game::FutureTone, &mut File::open(&output).unwrap(), false);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mut time = 0;
+        let mut timeline = Vec::new();
+
+        for command in merged.unwrap().command_buffer {
+            match command.meta.opcode {
+                Opcode::TIME => time = command.args[0],
+                opcode => timeline.push((time, opcode.name())),
+            }
         }
+
+        assert_eq!(
+            timeline,
+            vec![
+                (0, "MUSIC_PLAY"),
+                (100000, "LYRIC"),
+                (350000, "LYRIC"),
+                (500000, "CHANGE_FIELD"),
+                (500000, "PV_END"),
+                (500000, "END"),
+            ]
+        );
     }
 }
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use dsc_merger::common::{format_timestamp, get_end_command};
+use dsc_merger::{
+    diff, load_opcode_definitions, open_input, open_output, validate, ApplicationError,
+    ApplicationResult, DSCConverter, DumpOptions, Endianness, Game, InputSpec, Opcode,
+    OpcodeCategory, ScriptFormat, DSCVM,
+};
+
+pub fn load_opcode_tables(opcode_tables: &[String]) -> ApplicationResult {
+    for filename in opcode_tables {
+        match File::open(filename) {
+            Ok(mut file) => load_opcode_definitions(&mut file)?,
+            Err(_) => return Err(ApplicationError::FileNotFound(filename.to_owned())),
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads an encoded script, detecting its game from the header unless it is
+/// given with `-g` or an `@game` suffix.
+fn load_script(spec: &str, game: Option<Game>) -> ApplicationResult<(Game, DSCVM)> {
+    let input = InputSpec::parse(spec);
+
+    let mut file = open_input(&input.path)?;
+
+    let game = match input.game.or(game) {
+        Some(game) => game,
+        None => DSCVM::detect_game(&mut file, &input.path)?,
+    };
+
+    Ok((game, DSCVM::load(game, &mut file, false)?))
+}
+
+/// Loads a plaintext, JSON or CSV script. These don't say which game they
+/// are for, so they are assumed to be for Future Tone unless told otherwise.
+fn load_text_script(
+    spec: &str,
+    game: Option<Game>,
+    pad_missing_args: bool,
+) -> ApplicationResult<(Game, DSCVM)> {
+    let input = InputSpec::parse(spec);
+    let game = input.game.or(game).unwrap_or(Game::FutureTone);
+
+    let dsc_vm = DSCVM::load_text_file(game, Path::new(&input.path), false, pad_missing_args)?;
+
+    Ok((game, dsc_vm))
+}
+
+fn write_text(output: Option<&String>, text: &str) -> ApplicationResult {
+    match output {
+        Some(output) => Ok(open_output(output)?.write_all(text.as_bytes())?),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+pub fn dump(
+    input: &str,
+    output: Option<&String>,
+    game: Option<Game>,
+    format: Option<ScriptFormat>,
+    options: &DumpOptions,
+) -> ApplicationResult {
+    let (_, dsc_vm) = load_script(input, game)?;
+
+    let format = format.unwrap_or_else(|| match output {
+        Some(output) => ScriptFormat::from_path(output),
+        None => ScriptFormat::Plaintext,
+    });
+
+    let text = match format {
+        ScriptFormat::Plaintext => dsc_vm.dump_with(options),
+        ScriptFormat::Json | ScriptFormat::Csv => {
+            let mut buffer = Vec::new();
+
+            if format == ScriptFormat::Json {
+                dsc_vm.write_json(&mut buffer)?;
+            } else {
+                dsc_vm.write_csv(&mut buffer)?;
+            }
+
+            String::from_utf8_lossy(&buffer).into_owned()
+        }
+    };
+
+    write_text(output, &text)
+}
+
+pub fn compile(
+    input: &str,
+    output: &str,
+    game: Option<Game>,
+    endianness: Option<Endianness>,
+    pad_missing_args: bool,
+) -> ApplicationResult {
+    let (game, mut dsc_vm) = load_text_script(input, game, pad_missing_args)?;
+
+    // Binary scripts are terminated by END, which dumps don't always have.
+    if dsc_vm
+        .command_buffer
+        .last()
+        .map(|command| command.meta.opcode)
+        != Some(Opcode::END)
+    {
+        dsc_vm.add_command(get_end_command());
+    }
+
+    dsc_vm.endianness = endianness.unwrap_or_default();
+
+    dsc_vm.write(game, &mut open_output(output)?)
+}
+
+pub fn info(input: &str, game: Option<Game>) -> ApplicationResult {
+    let (game, dsc_vm) = load_script(input, game)?;
+
+    let mut counts: HashMap<Opcode, usize> = HashMap::new();
+
+    for command in &dsc_vm.command_buffer {
+        *counts.entry(command.meta.opcode).or_default() += 1;
+    }
+
+    let targets = counts.get(&Opcode::TARGET).copied().unwrap_or(0);
+
+    println!("File: {}", InputSpec::parse(input).path);
+    println!("Game: Project Diva {}", game);
+    println!("Byte order: {}", dsc_vm.endianness);
+
+    if let Some(container) = &dsc_vm.container {
+        println!(
+            "Container: PVSC (version {:#010x}, {} trailing byte(s))",
+            container.version,
+            container.footer.len()
+        );
+    }
+
+    println!("Commands: {}", dsc_vm.command_buffer.len());
+    println!("Targets: {}", targets);
+    println!("Duration: {}", format_timestamp(dsc_vm.duration()));
+
+    println!("Categories:");
+
+    for category in OpcodeCategory::ALL {
+        let count = counts
+            .iter()
+            .filter(|(opcode, _)| opcode.category() == Some(category))
+            .map(|(_, count)| count)
+            .sum::<usize>();
+
+        if count > 0 {
+            println!("  {}: {}", category, count);
+        }
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<(Opcode, usize)>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.name().cmp(b.0.name())));
+
+    println!("Opcodes:");
+
+    for (opcode, count) in counts {
+        println!("  {}: {}", opcode, count);
+    }
+
+    Ok(())
+}
+
+pub fn validate_files(
+    inputs: &[String],
+    plaintext: bool,
+    pad_missing_args: bool,
+    game: Option<Game>,
+) -> ApplicationResult {
+    let mut problems = 0;
+
+    for input in inputs {
+        let (_, dsc_vm) = if plaintext {
+            load_text_script(input, game, pad_missing_args)?
+        } else {
+            load_script(input, game)?
+        };
+
+        let issues = validate(&dsc_vm);
+
+        if issues.is_empty() {
+            println!("{}: OK", input);
+        }
+
+        for issue in &issues {
+            println!("{}: {}", input, issue);
+        }
+
+        problems += issues.len();
+    }
+
+    if problems > 0 {
+        return Err(ApplicationError::ValidationFailed(problems));
+    }
+
+    Ok(())
+}
+
+pub fn diff_files(old: &str, new: &str, game: Option<Game>) -> ApplicationResult {
+    let (old_game, old_dsc) = load_script(old, game)?;
+    let (new_game, new_dsc) = load_script(new, game)?;
+
+    // Scripts for different games are compared in the layout of the first.
+    let mut converter = DSCConverter::new(old_game);
+    let new_dsc = converter.convert(new_game, new_dsc);
+
+    let entries = diff(&old_dsc, &new_dsc);
+
+    for entry in &entries {
+        println!("@ {} (TIME({}))", format_timestamp(entry.time), entry.time);
+
+        for command in &entry.removed {
+            println!("- {}", command);
+        }
+
+        for command in &entry.added {
+            println!("+ {}", command);
+        }
+    }
+
+    if entries.is_empty() {
+        println!("The scripts are identical.");
+    }
+
+    Ok(())
+}
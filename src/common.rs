@@ -1,15 +1,18 @@
 use std::fmt::{Display, Formatter};
+use std::io::{Read, Result as IOResult, Write};
 
+use byteorder::{ReadBytesExt, WriteBytesExt, BE, LE};
 use subparse::timetypes::TimePoint;
 
 use crate::error::{ApplicationError, ApplicationResult};
 use crate::opcodes::{Command, Opcode, OpcodeMeta};
 
-pub const GAME_MAP: [(&'static str, Game); 4] = [
+pub const GAME_MAP: [(&str, Game); 5] = [
     (
-        "Project Diva AFT / Future Tone / Mega Mix / Mega Mix+",
+        "Project Diva Future Tone / Mega Mix / Mega Mix+",
         Game::FutureTone,
     ),
+    ("Project Diva Arcade Future Tone", Game::Arcade),
     (
         "Project Diva F / Dreamy Theater 2nd / Dreamy Theater Extend / f",
         Game::F,
@@ -18,25 +21,80 @@ pub const GAME_MAP: [(&'static str, Game); 4] = [
     ("Project Diva X", Game::X),
 ];
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Game {
     F,
     F2nd,
     X,
     FutureTone,
+    /// Arcade Future Tone. Its scripts are the same as Future Tone ones, so
+    /// this is an alias that uses the Future Tone header and opcode table.
+    /// Scripts from the Arcade releases before Future Tone aren't supported.
     Arcade,
 }
 
 impl Game {
-    pub fn to_string(&self) -> String {
+    pub fn from_string(game: &str) -> Option<Self> {
+        match game.to_lowercase().as_str() {
+            "f" => Some(Game::F),
+            "f2" | "f2nd" | "f 2nd" => Some(Game::F2nd),
+            "x" => Some(Game::X),
+            "ft" | "futuretone" | "future tone" => Some(Game::FutureTone),
+            "arcade" | "aft" => Some(Game::Arcade),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Game::F => write!(f, "F"),
+            Game::F2nd => write!(f, "F2nd"),
+            Game::X => write!(f, "X"),
+            Game::FutureTone => write!(f, "Future Tone"),
+            Game::Arcade => write!(f, "Arcade Future Tone"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn from_string(endianness: &str) -> Option<Self> {
+        match endianness.to_lowercase().as_str() {
+            "le" | "little" => Some(Endianness::Little),
+            "be" | "big" => Some(Endianness::Big),
+            _ => None,
+        }
+    }
+
+    pub fn read_i32<R: Read>(&self, reader: &mut R) -> IOResult<i32> {
+        match self {
+            Endianness::Little => reader.read_i32::<LE>(),
+            Endianness::Big => reader.read_i32::<BE>(),
+        }
+    }
+
+    pub fn write_i32<W: Write>(&self, writer: &mut W, value: i32) -> IOResult<()> {
         match self {
-            Game::F => "F",
-            Game::F2nd => "F2nd",
-            Game::X => "X",
-            Game::FutureTone => "Future Tone",
-            Game::Arcade => "Arcade",
+            Endianness::Little => writer.write_i32::<LE>(value),
+            Endianness::Big => writer.write_i32::<BE>(value),
+        }
+    }
+}
+
+impl Display for Endianness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endianness::Little => write!(f, "little-endian"),
+            Endianness::Big => write!(f, "big-endian"),
         }
-        .to_string()
     }
 }
 
@@ -127,7 +185,16 @@ pub fn get_time_command(time: i32) -> Command {
     let param_count: usize = 1;
 
     let meta = OpcodeMeta::new(id, opcode, param_count);
-    return Command::new(meta, vec![time]);
+    Command::new(meta, vec![time])
+}
+
+pub fn get_end_command() -> Command {
+    let id = 0;
+    let opcode = Opcode::END;
+    let param_count: usize = 0;
+
+    let meta = OpcodeMeta::new(id, opcode, param_count);
+    Command::new(meta, vec![])
 }
 
 pub fn get_lyric_command(idx: i32, mode: i32) -> Command {
@@ -136,7 +203,7 @@ pub fn get_lyric_command(idx: i32, mode: i32) -> Command {
     let param_count: usize = 2;
 
     let meta = OpcodeMeta::new(id, opcode, param_count);
-    return Command::new(meta, vec![idx, mode]);
+    Command::new(meta, vec![idx, mode])
 }
 
 pub fn timestamp_to_millis(ts: TimePoint) -> i32 {
@@ -147,7 +214,7 @@ pub fn timestamp_to_millis(ts: TimePoint) -> i32 {
     let minutes_millis = minutes as i32 * 60 * 1000;
     let seconds_millis = seconds as i32 * 1000;
 
-    return minutes_millis + seconds_millis + milliseconds as i32;
+    minutes_millis + seconds_millis + milliseconds as i32
 }
 
 pub fn parse_challenge_time_timestamp(timestamp: &str) -> ApplicationResult<i32> {
@@ -175,7 +242,7 @@ pub fn parse_challenge_time_timestamp(timestamp: &str) -> ApplicationResult<i32>
     let minutes = minutes.unwrap();
     let seconds = seconds.unwrap();
 
-    if minutes < 0 || minutes > 99 || seconds < 0 || seconds > 59 {
+    if !(0..=99).contains(&minutes) || !(0..=59).contains(&seconds) {
         return Err(ApplicationError::InvalidTimestamp(timestamp.to_string()));
     }
 
@@ -187,12 +254,26 @@ pub fn parse_challenge_time_timestamp(timestamp: &str) -> ApplicationResult<i32>
 
     let milliseconds = milliseconds.unwrap();
 
-    if milliseconds < 0 || milliseconds > 999 {
+    if !(0..=999).contains(&milliseconds) {
         return Err(ApplicationError::InvalidTimestamp(timestamp.to_string()));
     }
 
     let minutes_millis = minutes * 60 * 1000;
     let seconds_millis = seconds * 1000;
 
-    return Ok(minutes_millis + seconds_millis + milliseconds);
+    Ok(minutes_millis + seconds_millis + milliseconds)
+}
+
+/// Formats a DSC time (in 1/100000ths of a second) as `MM:SS.mmm`.
+pub fn format_timestamp(time: i32) -> String {
+    let sign = if time < 0 { "-" } else { "" };
+    let millis = time.unsigned_abs() / 100;
+
+    format!(
+        "{}{:02}:{:02}.{:03}",
+        sign,
+        millis / 60000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
 }
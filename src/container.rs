@@ -0,0 +1,278 @@
+use std::io::{Read, Write};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+
+use crate::common::Endianness;
+use crate::error::ApplicationResult;
+
+pub const PVSC_SIGNATURE: i32 = 1129535056;
+pub const EOFC_SIGNATURE: i32 = 1128681285;
+
+const DATA_OFFSET: u32 = 0x40;
+const DEFAULT_FLAGS: u32 = 0x10000000;
+const BIG_ENDIAN_FLAG: u32 = 0x08000000;
+const SECTION_ALIGNMENT: usize = 16;
+
+/// The section container F2nd and X wrap their scripts in. The header and
+/// the trailing sections (starting at `EOFC`) are kept as they were read so
+/// that an unchanged script is written back byte for byte.
+///
+/// The section header itself is always little-endian; a flag in it tells
+/// whether the script data that follows is big-endian.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PVSCContainer {
+    pub section_size: u32,
+    pub data_offset: u32,
+    pub flags: u32,
+    pub depth: u32,
+    pub data_size: u32,
+    pub reserved: [u32; 10],
+    pub version: i32,
+    pub unknown: i32,
+    pub footer: Vec<u8>,
+
+    // Length of the section data as loaded, used to adjust the size fields
+    // when the script grows or shrinks.
+    data_length: u32,
+}
+
+impl Default for PVSCContainer {
+    fn default() -> Self {
+        let mut footer = Vec::new();
+
+        for word in [EOFC_SIGNATURE, 0, 0x20, DEFAULT_FLAGS as i32, 0, 0, 0, 0] {
+            footer.extend_from_slice(&word.to_le_bytes());
+        }
+
+        Self {
+            section_size: 0,
+            data_offset: DATA_OFFSET,
+            flags: DEFAULT_FLAGS,
+            depth: 0,
+            data_size: 0,
+            reserved: [0; 10],
+            version: 0,
+            unknown: 0,
+            footer,
+            data_length: 0,
+        }
+    }
+}
+
+impl PVSCContainer {
+    /// Size of the header (including the two script words) in bytes.
+    pub const HEADER_SIZE: u64 = 72;
+
+    /// An empty container for a new script that starts with `version`.
+    pub fn with_version(version: i32) -> Self {
+        Self {
+            version,
+            ..Self::default()
+        }
+    }
+
+    /// Reads the header, leaving the reader at the first command. The
+    /// signature is expected to have been consumed already.
+    pub fn read_header<R: Read>(reader: &mut R) -> ApplicationResult<Self> {
+        let section_size = reader.read_u32::<LE>()?;
+        let data_offset = reader.read_u32::<LE>()?;
+        let flags = reader.read_u32::<LE>()?;
+        let depth = reader.read_u32::<LE>()?;
+        let data_size = reader.read_u32::<LE>()?;
+
+        let mut reserved = [0; 10];
+        reader.read_u32_into::<LE>(&mut reserved)?;
+
+        let mut container = Self {
+            section_size,
+            data_offset,
+            flags,
+            depth,
+            data_size,
+            reserved,
+            version: 0,
+            unknown: 0,
+            footer: Vec::new(),
+            data_length: 0,
+        };
+
+        let endianness = container.endianness();
+        container.version = endianness.read_i32(reader)?;
+        container.unknown = endianness.read_i32(reader)?;
+
+        Ok(container)
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        if self.flags & BIG_ENDIAN_FLAG != 0 {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        }
+    }
+
+    /// Reads everything from the `EOFC` section to the end of the file and
+    /// records how long the script data was. `script_end` is the offset
+    /// right after the last byte of the script, before any zero padding.
+    /// The footer is copied as-is regardless of the script's byte order.
+    pub fn read_footer<R: Read>(&mut self, reader: &mut R, script_end: u64) -> ApplicationResult {
+        let mut position = script_end;
+
+        loop {
+            match reader.read_i32::<LE>() {
+                Ok(0) => position += 4,
+                Ok(EOFC_SIGNATURE) => {
+                    self.footer = EOFC_SIGNATURE.to_le_bytes().to_vec();
+                    reader.read_to_end(&mut self.footer)?;
+                    break;
+                }
+                // Anything else (including a missing footer) means there is
+                // nothing more we know how to preserve.
+                _ => break,
+            }
+        }
+
+        self.data_length = position.saturating_sub(self.data_offset as u64) as u32;
+
+        Ok(())
+    }
+
+    /// Whether the script data that was read ends where the header says it
+    /// does. Reading a script with the wrong opcode table usually stops
+    /// somewhere else. Headers without a data size match any length.
+    pub fn matches_data_size(&self) -> bool {
+        self.data_size == 0 || self.data_length == self.data_size
+    }
+
+    /// `EOFC` is a byte string, so it has to be recognized in both orders
+    /// when scanning the commands of a script.
+    pub fn is_footer_signature(word: i32) -> bool {
+        word == EOFC_SIGNATURE || word == EOFC_SIGNATURE.swap_bytes()
+    }
+
+    /// Returns the amount of zero padding needed after `script_length`
+    /// bytes of commands to keep the footer aligned.
+    pub fn padding(&self, script_length: usize) -> usize {
+        let data_length = self.unpadded_data_length(script_length);
+        (SECTION_ALIGNMENT - data_length % SECTION_ALIGNMENT) % SECTION_ALIGNMENT
+    }
+
+    /// Length of the section data (which starts at `data_offset`) when it
+    /// holds `script_length` bytes of commands.
+    fn unpadded_data_length(&self, script_length: usize) -> usize {
+        (Self::HEADER_SIZE as usize + script_length).saturating_sub(self.data_offset as usize)
+    }
+
+    pub fn write_header<W: Write>(
+        &self,
+        writer: &mut W,
+        script_length: usize,
+        endianness: Endianness,
+    ) -> ApplicationResult {
+        let data_length = self.unpadded_data_length(script_length) + self.padding(script_length);
+        let delta = data_length as i64 - self.data_length as i64;
+
+        let flags = match endianness {
+            Endianness::Little => self.flags & !BIG_ENDIAN_FLAG,
+            Endianness::Big => self.flags | BIG_ENDIAN_FLAG,
+        };
+
+        writer.write_i32::<LE>(PVSC_SIGNATURE)?;
+        writer.write_u32::<LE>((self.section_size as i64 + delta) as u32)?;
+        writer.write_u32::<LE>(self.data_offset)?;
+        writer.write_u32::<LE>(flags)?;
+        writer.write_u32::<LE>(self.depth)?;
+        writer.write_u32::<LE>((self.data_size as i64 + delta) as u32)?;
+
+        for word in self.reserved {
+            writer.write_u32::<LE>(word)?;
+        }
+
+        endianness.write_i32(writer, self.version)?;
+        endianness.write_i32(writer, self.unknown)?;
+
+        Ok(())
+    }
+
+    pub fn write_footer<W: Write>(
+        &self,
+        writer: &mut W,
+        script_length: usize,
+    ) -> ApplicationResult {
+        for _ in 0..self.padding(script_length) {
+            writer.write_u8(0)?;
+        }
+
+        writer.write_all(&self.footer)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::common::Game;
+    use crate::dsc::DSCVM;
+
+    /// Builds an X script with the given command words, padded and followed
+    /// by the default footer the way the game's files are.
+    fn script(commands: &[i32]) -> Vec<u8> {
+        let container = PVSCContainer::default();
+        let script_length = commands.len() * 4;
+        let data_length =
+            container.unpadded_data_length(script_length) + container.padding(script_length);
+
+        let mut words = vec![PVSC_SIGNATURE, 0x1234, DATA_OFFSET as i32];
+        words.extend([DEFAULT_FLAGS as i32, 0, data_length as i32]);
+        words.extend([0; 10]);
+        words.extend([0x1311_0303, 0]);
+        words.extend(commands);
+
+        let mut bytes = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        bytes.resize(bytes.len() + container.padding(script_length), 0);
+        bytes.extend(&container.footer);
+
+        bytes
+    }
+
+    fn round_trip(bytes: &[u8]) -> Vec<u8> {
+        let dsc_vm = DSCVM::load(Game::X, &mut Cursor::new(bytes), false).unwrap();
+
+        let mut written = Vec::new();
+        dsc_vm.write(Game::X, &mut written).unwrap();
+
+        written
+    }
+
+    #[test]
+    fn unchanged_script_is_written_back_byte_for_byte() {
+        // TIME(0); MUSIC_PLAY(); TIME(100000); END();
+        let bytes = script(&[1, 0, 25, 1, 100000, 0]);
+
+        assert_eq!(round_trip(&bytes), bytes);
+    }
+
+    #[test]
+    fn script_without_end_is_written_back_byte_for_byte() {
+        // TIME(0); MUSIC_PLAY(); TIME(100000); MUSIC_PLAY(); followed
+        // directly by EOFC, with no padding in between
+        let bytes = script(&[1, 0, 25, 1, 100000, 25]);
+
+        assert_eq!(round_trip(&bytes), bytes);
+    }
+
+    #[test]
+    fn data_size_is_checked() {
+        let bytes = script(&[1, 0, 25, 0]);
+        let dsc_vm = DSCVM::load(Game::X, &mut Cursor::new(&bytes), false).unwrap();
+
+        assert!(dsc_vm.container.unwrap().matches_data_size());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::common::Game;
+use crate::dsc::{pvsc_version, DSCVM};
+use crate::opcodes::{Command, Opcode, OpcodeMeta, TargetDefaults};
+
+/// Re-encodes scripts made for other games so they can be written for the
+/// target game. Commands without an equivalent in the target game are
+/// dropped, and commands whose parameter count differs without a known
+/// mapping are truncated or padded with zeros. Both are recorded so they can
+/// be reported.
+///
+/// `TARGET` parameters are mapped by name. The ones only some games have are
+/// filled in from what the script says elsewhere where possible (see
+/// [`TargetDefaults`]); parameters that had to be made up or
+/// whose value is lost are recorded as well. Target `type` ids are copied
+/// as they are.
+pub struct DSCConverter {
+    to: Game,
+
+    // Defaults for the script being converted.
+    target_defaults: TargetDefaults,
+
+    pub dropped: Vec<(Opcode, usize)>,
+    pub resized: Vec<(Opcode, usize)>,
+    /// `TARGET` parameters that the target game doesn't have and whose
+    /// value was lost, i.e. wasn't the default (or 0 if there is none).
+    pub lost_fields: Vec<(&'static str, usize)>,
+    /// `TARGET` parameters that the source game doesn't have and that had to
+    /// be set to 0.
+    pub invented_fields: Vec<(&'static str, usize)>,
+}
+
+impl DSCConverter {
+    pub fn new(to: Game) -> Self {
+        Self {
+            to,
+            target_defaults: TargetDefaults::default(),
+            dropped: Vec::new(),
+            resized: Vec::new(),
+            lost_fields: Vec::new(),
+            invented_fields: Vec::new(),
+        }
+    }
+
+    fn record<T: PartialEq>(list: &mut Vec<(T, usize)>, key: T) {
+        match list.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, count)) => *count += 1,
+            None => list.push((key, 1)),
+        }
+    }
+
+    /// Maps parameters by name, since the games order the `TARGET` parameters
+    /// differently.
+    fn convert_args_by_name(
+        &mut self,
+        from: &OpcodeMeta,
+        to: &OpcodeMeta,
+        args: &[i32],
+    ) -> Vec<i32> {
+        let position =
+            |meta: &OpcodeMeta, name: &str| meta.params.iter().position(|param| param.name == name);
+
+        for (param, &value) in from.params.iter().zip(args) {
+            if position(to, param.name).is_none()
+                && self.target_defaults.get(param.name).unwrap_or(0) != value
+            {
+                Self::record(&mut self.lost_fields, param.name);
+            }
+        }
+
+        to.params
+            .iter()
+            .map(
+                |param| match position(from, param.name).and_then(|idx| args.get(idx)) {
+                    Some(&value) => value,
+                    None => self.target_defaults.get(param.name).unwrap_or_else(|| {
+                        Self::record(&mut self.invented_fields, param.name);
+                        0
+                    }),
+                },
+            )
+            .collect()
+    }
+
+    pub fn convert_command(&mut self, command: Command) -> Option<Command> {
+        let opcode = command.meta.opcode;
+        self.target_defaults.track(&command);
+
+        let meta = match Command::get_opcode_meta_from_opcode(self.to, opcode) {
+            Some(meta) => meta,
+            None => {
+                Self::record(&mut self.dropped, opcode);
+                return None;
+            }
+        };
+
+        let args = if opcode == Opcode::TARGET && !command.meta.params.is_empty() {
+            self.convert_args_by_name(&command.meta, &meta, &command.args)
+        } else if command.args.len() != meta.param_count {
+            Self::record(&mut self.resized, opcode);
+
+            let mut args = command.args;
+            args.resize(meta.param_count, 0);
+            args
+        } else {
+            command.args
+        };
+
+        Some(Command::new(meta, args))
+    }
+
+    pub fn convert(&mut self, from: Game, dsc_vm: DSCVM) -> DSCVM {
+        if from == self.to {
+            return dsc_vm;
+        }
+
+        self.target_defaults = TargetDefaults::default();
+
+        let mut converted = DSCVM::new(dsc_vm.remove_targets);
+        converted.offset = dsc_vm.offset;
+        converted.filters = dsc_vm.filters.clone();
+        converted.container = dsc_vm.container;
+
+        // The version word tells F2nd and X scripts apart, so it has to
+        // follow the game.
+        if let (Some(container), Some(version)) =
+            (converted.container.as_mut(), pvsc_version(self.to))
+        {
+            container.version = version;
+        }
+        converted.endianness = dsc_vm.endianness;
+
+        for command in dsc_vm.command_buffer {
+            if let Some(command) = self.convert_command(command) {
+                converted.add_command(command);
+            }
+        }
+
+        converted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(game: Game, name: &str, args: Vec<i32>) -> Command {
+        let meta = Command::get_opcode_meta_from_name(game, name.to_string()).unwrap();
+        Command::new(meta, args)
+    }
+
+    fn script(game: Game, commands: &[(&str, Vec<i32>)]) -> DSCVM {
+        let mut dsc_vm = DSCVM::new(false);
+
+        for (name, args) in commands {
+            dsc_vm.add_command(command(game, name, args.clone()));
+        }
+
+        dsc_vm
+    }
+
+    fn args_of(dsc_vm: &DSCVM, opcode: Opcode) -> Vec<Vec<i32>> {
+        dsc_vm
+            .command_buffer
+            .iter()
+            .filter(|command| command.meta.opcode == opcode)
+            .map(|command| command.args.clone())
+            .collect()
+    }
+
+    #[test]
+    fn ft_to_x() {
+        let move_camera =
+            Command::get_opcode_meta_from_name(Game::FutureTone, "MOVE_CAMERA".into())
+                .unwrap()
+                .param_count;
+
+        let ft = script(
+            Game::FutureTone,
+            &[
+                ("TIME", vec![0]),
+                ("BAR_TIME_SET", vec![120, 3]),
+                ("TARGET_FLYING_TIME", vec![1200]),
+                ("CHANGE_FIELD", vec![1]),
+                ("MOVE_CAMERA", vec![0; move_camera]),
+                ("TARGET", vec![0, 480000, 240000, 100, 500, 200, 2]),
+            ],
+        );
+
+        let mut converter = DSCConverter::new(Game::X);
+        let x = converter.convert(Game::FutureTone, ft);
+
+        assert_eq!(
+            args_of(&x, Opcode::TARGET),
+            vec![vec![
+                0, -1, -1, 480000, 240000, 100, 2, 500, 200, 1200, 3, 0
+            ]]
+        );
+        assert_eq!(args_of(&x, Opcode::new("CHANGE_FIELD")), vec![vec![1, 0]]);
+
+        assert_eq!(converter.dropped, vec![(Opcode::new("MOVE_CAMERA"), 1)]);
+        assert_eq!(converter.resized, vec![(Opcode::new("CHANGE_FIELD"), 1)]);
+        assert_eq!(converter.lost_fields, vec![]);
+        assert_eq!(converter.invented_fields, vec![("unknown", 1)]);
+    }
+
+    #[test]
+    fn x_to_ft() {
+        let x = script(
+            Game::X,
+            &[
+                ("TIME", vec![0]),
+                ("TARGET_FLYING_TIME", vec![1200]),
+                ("BAR_POINT", vec![1]),
+                // Only what FT stores as well, so nothing is lost.
+                (
+                    "TARGET",
+                    vec![0, -1, -1, 480000, 240000, 100, 2, 500, 200, 1200, 0, 0],
+                ),
+                (
+                    "TARGET",
+                    vec![1, 500, 1000, 480000, 240000, 100, 2, 500, 200, 900, 3, 7],
+                ),
+            ],
+        );
+
+        let mut converter = DSCConverter::new(Game::FutureTone);
+        let ft = converter.convert(Game::X, x);
+
+        assert_eq!(
+            args_of(&ft, Opcode::TARGET),
+            vec![
+                vec![0, 480000, 240000, 100, 500, 200, 2],
+                vec![1, 480000, 240000, 100, 500, 200, 2],
+            ]
+        );
+
+        assert_eq!(converter.dropped, vec![(Opcode::new("BAR_POINT"), 1)]);
+        assert_eq!(converter.resized, vec![]);
+        assert_eq!(
+            converter.lost_fields,
+            vec![
+                ("hold_length", 1),
+                ("hold_end", 1),
+                ("flying_time", 1),
+                ("time_signature", 1),
+                ("unknown", 1),
+            ]
+        );
+        assert_eq!(converter.invented_fields, vec![]);
+    }
+}
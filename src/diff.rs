@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use crate::dsc::DSCVM;
+use crate::opcodes::Command;
+
+/// The commands that differ between two scripts at a single point in time.
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    pub time: i32,
+    pub removed: Vec<Command>,
+    pub added: Vec<Command>,
+}
+
+/// Compares two scripts event by event. Commands are matched by opcode and
+/// arguments, regardless of their order within the same time.
+pub fn diff(old: &DSCVM, new: &DSCVM) -> Vec<DiffEntry> {
+    let by_time = |dsc_vm: &DSCVM| {
+        dsc_vm
+            .events()
+            .into_iter()
+            .map(|event| (event.time, event.commands))
+            .collect::<BTreeMap<i32, Vec<Command>>>()
+    };
+
+    let mut old_events = by_time(old);
+    let mut new_events = by_time(new);
+
+    let mut times = old_events
+        .keys()
+        .chain(new_events.keys())
+        .copied()
+        .collect::<Vec<i32>>();
+
+    times.sort();
+    times.dedup();
+
+    let mut entries = Vec::new();
+
+    for time in times {
+        let mut added = new_events.remove(&time).unwrap_or_default();
+        let mut removed = Vec::new();
+
+        for command in old_events.remove(&time).unwrap_or_default() {
+            match added.iter().position(|other| other == &command) {
+                Some(idx) => {
+                    added.remove(idx);
+                }
+                None => removed.push(command),
+            }
+        }
+
+        if !removed.is_empty() || !added.is_empty() {
+            entries.push(DiffEntry {
+                time,
+                removed,
+                added,
+            });
+        }
+    }
+
+    entries
+}
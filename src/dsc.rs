@@ -1,18 +1,63 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Seek, SeekFrom},
-};
-
-use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
 
+use crate::common::{get_time_command, Endianness, Game};
+use crate::container::{PVSCContainer, PVSC_SIGNATURE};
 use crate::error::{ApplicationError, ApplicationResult};
-use crate::opcodes::Command;
+use crate::export::{read_csv, read_json, write_csv, write_json, ScriptFormat};
+use crate::filter::OpcodeFilter;
+use crate::input::{open_input, STDIO_PATH};
+use crate::logger::Logger;
+use crate::merger::Event;
+use crate::opcodes::{Command, Opcode};
+use crate::plaintext::{format_script, DumpOptions, PlaintextParser};
 use crate::subtitle::{SubtitleFile, SubtitleKind};
-use crate::{common::Game, logger::Logger};
+
+const F_SIGNATURE: i32 = 302121504;
+const FT_SIGNATURE: i32 = 335874337;
+
+// The first word of F2nd and X script data. Like the F and FT signatures,
+// these are build dates.
+const F2ND_VERSION: i32 = 0x13120420;
+const X_VERSION: i32 = 0x15122517;
+
+/// The version word that scripts for `game` start with, for the games that
+/// use the PVSC container.
+pub fn pvsc_version(game: Game) -> Option<i32> {
+    match game {
+        Game::F2nd => Some(F2ND_VERSION),
+        Game::X => Some(X_VERSION),
+        Game::F | Game::FutureTone | Game::Arcade => None,
+    }
+}
 
 pub struct DSCVM {
     pub command_buffer: Vec<Command>,
     pub remove_targets: bool,
+    /// Moves every command by this many ticks when merged.
+    pub offset: i32,
+    /// Decide which commands are merged, see [`OpcodeFilter`].
+    pub filters: Vec<OpcodeFilter>,
+    pub container: Option<PVSCContainer>,
+    pub endianness: Endianness,
+}
+
+/// Reads the signature at the start of a binary script along with the byte
+/// order it was stored in. Signatures that aren't recognized in either order
+/// are returned as little-endian.
+fn read_signature<R: Read>(reader: &mut R) -> ApplicationResult<(i32, Endianness)> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+
+    let swapped = i32::from_be_bytes(bytes);
+
+    if swapped == F_SIGNATURE || swapped == FT_SIGNATURE {
+        return Ok((swapped, Endianness::Big));
+    }
+
+    Ok((i32::from_le_bytes(bytes), Endianness::Little))
 }
 
 impl DSCVM {
@@ -20,6 +65,10 @@ impl DSCVM {
         Self {
             command_buffer: Vec::new(),
             remove_targets,
+            offset: 0,
+            filters: Vec::new(),
+            container: None,
+            endianness: Endianness::Little,
         }
     }
 
@@ -27,21 +76,108 @@ impl DSCVM {
         self.command_buffer.push(command);
     }
 
-    pub fn load(game: Game, file: &mut File, remove_targets: bool) -> ApplicationResult<Self> {
+    /// Infers which games a binary DSC script could have been made for from
+    /// its header signature. F2nd and X share the same container, so those
+    /// are told apart by checking which opcode tables the script parses with
+    /// up to exactly the end of the data the header describes. Scripts that
+    /// only use opcodes both games share read fine either way; for those the
+    /// version word decides if it is a known one, and both games are returned
+    /// otherwise. Returns no games if the signature is not recognized.
+    pub fn possible_games<R: Read + Seek>(reader: &mut R) -> ApplicationResult<Vec<Game>> {
+        reader.seek(SeekFrom::Start(0))?;
+
+        let (signature, _) = read_signature(reader)?;
+
+        let games = match signature {
+            F_SIGNATURE => vec![Game::F],
+            FT_SIGNATURE => vec![Game::FutureTone],
+            PVSC_SIGNATURE => {
+                let readable = [Game::F2nd, Game::X]
+                    .into_iter()
+                    .filter_map(|game| match DSCVM::load(game, reader, false) {
+                        Ok(DSCVM {
+                            container: Some(container),
+                            ..
+                        }) if container.matches_data_size() => Some((game, container.version)),
+                        _ => None,
+                    })
+                    .collect::<Vec<(Game, i32)>>();
+
+                match readable
+                    .iter()
+                    .find(|(game, version)| pvsc_version(*game) == Some(*version))
+                {
+                    Some(&(game, _)) => vec![game],
+                    None => readable.into_iter().map(|(game, _)| game).collect(),
+                }
+            }
+            _ => Vec::new(),
+        };
+
+        reader.seek(SeekFrom::Start(0))?;
+
+        Ok(games)
+    }
+
+    /// Picks the game for a script that reads the same for several games.
+    /// F2nd wins over X, since X charts rarely get by without X's own
+    /// opcodes.
+    pub fn preferred_game(games: &[Game]) -> Option<Game> {
+        games
+            .iter()
+            .copied()
+            .find(|&game| game == Game::F2nd)
+            .or(games.first().copied())
+    }
+
+    /// Like [`DSCVM::possible_games`], but settles on a single game with
+    /// [`DSCVM::preferred_game`]. `name` is used in the error.
+    pub fn detect_game<R: Read + Seek>(reader: &mut R, name: &str) -> ApplicationResult<Game> {
+        match DSCVM::preferred_game(&DSCVM::possible_games(reader)?) {
+            Some(game) => Ok(game),
+            None => Err(ApplicationError::UndetectableGame(name.to_owned())),
+        }
+    }
+
+    pub fn load<R: Read + Seek>(
+        game: Game,
+        reader: &mut R,
+        remove_targets: bool,
+    ) -> ApplicationResult<Self> {
         let mut command_buffer = Vec::new();
 
-        let skip = match game {
-            Game::F | Game::FutureTone => 4,
-            Game::F2nd | Game::X => 72,
-            _ => 0,
+        reader.seek(SeekFrom::Start(0))?;
+
+        let mut reader = BufReader::new(reader);
+
+        let (_, mut endianness) = read_signature(&mut reader)?;
+
+        let mut container = match game {
+            Game::F2nd | Game::X => Some(PVSCContainer::read_header(&mut reader)?),
+            Game::F | Game::FutureTone | Game::Arcade => None,
         };
 
-        file.seek(SeekFrom::Start(skip as u64))?;
+        if let Some(container) = &container {
+            endianness = container.endianness();
+        }
 
         loop {
-            let opcode = file.read_i32::<LE>()?;
+            // A script cut off right after a command simply has no `END`,
+            // which is for the validator to report rather than a read error.
+            if reader.fill_buf()?.is_empty() {
+                break;
+            }
+
+            let opcode = endianness.read_i32(&mut reader)?;
+
+            if opcode == 0 {
+                let end = Command::get_opcode_meta(game, 0)?;
+                command_buffer.push(Command::new(end, vec![]));
+                break;
+            }
 
-            if opcode == 0 || (opcode == 1128681285 && (game == Game::F2nd || game == Game::X)) {
+            if container.is_some() && PVSCContainer::is_footer_signature(opcode) {
+                reader.seek_relative(-4)?;
                 break;
             }
 
@@ -50,99 +186,152 @@ impl DSCVM {
             let mut args = Vec::new();
 
             for _ in 0..opcode_meta.param_count {
-                let arg = file.read_i32::<LE>()?;
+                let arg = endianness.read_i32(&mut reader)?;
                 args.push(arg);
             }
 
             command_buffer.push(Command::new(opcode_meta, args));
         }
 
-        let end = Command::get_opcode_meta(game, 0).unwrap();
-
-        command_buffer.push(Command::new(end, vec![]));
+        if let Some(container) = container.as_mut() {
+            let script_end = reader.stream_position()?;
+            container.read_footer(&mut reader, script_end)?;
+        }
 
         Ok(Self {
             command_buffer,
             remove_targets,
+            offset: 0,
+            filters: Vec::new(),
+            container,
+            endianness,
         })
     }
 
-    pub fn load_plaintext(
+    pub fn load_plaintext<R: Read>(
         game: Game,
-        file: &mut File,
+        reader: &mut R,
         remove_targets: bool,
+        pad_missing_args: bool,
     ) -> ApplicationResult<Self> {
-        let mut command_buffer = Vec::new();
+        let mut source = String::new();
+        reader.read_to_string(&mut source)?;
 
-        let reader = BufReader::new(file);
+        let parser = PlaintextParser::new(game, &source).pad_missing_args(pad_missing_args);
 
-        for line in reader.lines() {
-            let normalized_line = line
-                .unwrap_or("".to_string())
-                .trim()
-                .replace(")", "")
-                .replace(";", "")
-                .replace(" ", "");
-
-            if normalized_line.len() == 0 || normalized_line.starts_with("#") {
-                continue;
-            }
-
-            let components = normalized_line.split("(").collect::<Vec<&str>>();
+        Self::from_plaintext(parser, remove_targets)
+    }
 
-            if components.len() != 2 {
-                continue;
-            }
+    /// Loads a plaintext script from a file, resolving `#include`s relative
+    /// to it and naming it in syntax errors.
+    pub fn load_plaintext_file(
+        game: Game,
+        path: &Path,
+        remove_targets: bool,
+        pad_missing_args: bool,
+    ) -> ApplicationResult<Self> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return Err(ApplicationError::FileNotFound(path.display().to_string())),
+        };
 
-            let opcode_name = components[0];
+        let mut source = String::new();
+        file.read_to_string(&mut source)?;
 
-            let opcode_meta = Command::get_opcode_meta_from_name(game, opcode_name.to_owned());
+        let parser = PlaintextParser::new(game, &source)
+            .pad_missing_args(pad_missing_args)
+            .path(path);
 
-            match opcode_meta {
-                Ok(opcode_meta) => {
-                    let opcode_args_list = components[1].split(",").collect::<Vec<&str>>();
+        Self::from_plaintext(parser, remove_targets)
+    }
 
-                    let mut args = Vec::new();
+    /// Loads a text script from a file (or standard input for `-`), telling
+    /// plaintext, JSON and CSV apart by the extension.
+    pub fn load_text_file(
+        game: Game,
+        path: &Path,
+        remove_targets: bool,
+        pad_missing_args: bool,
+    ) -> ApplicationResult<Self> {
+        // Standard input has no extension to go by, so it has to be plaintext.
+        if path == Path::new(STDIO_PATH) {
+            let mut reader = open_input(STDIO_PATH)?;
+            return Self::load_plaintext(game, &mut reader, remove_targets, pad_missing_args);
+        }
 
-                    for raw_arg in opcode_args_list.iter() {
-                        match raw_arg.parse::<i32>() {
-                            Ok(arg) => {
-                                args.push(arg);
-                            }
-                            Err(_) => {
-                                return Err(ApplicationError::ArgumentParseError(
-                                    opcode_name.to_string(),
-                                    raw_arg.to_string(),
-                                ));
-                            }
-                        }
-                    }
+        let open = || match File::open(path) {
+            Ok(file) => Ok(BufReader::new(file)),
+            Err(_) => Err(ApplicationError::FileNotFound(path.display().to_string())),
+        };
 
-                    command_buffer.push(Command::new(opcode_meta, args));
-                }
-                Err(err) => {
-                    return Err(err);
-                }
+        match ScriptFormat::from_path(&path.to_string_lossy()) {
+            ScriptFormat::Plaintext => {
+                Self::load_plaintext_file(game, path, remove_targets, pad_missing_args)
             }
+            ScriptFormat::Json => Self::load_json(game, &mut open()?, remove_targets),
+            ScriptFormat::Csv => Self::load_csv(game, &mut open()?, remove_targets),
         }
+    }
+
+    fn from_plaintext(parser: PlaintextParser, remove_targets: bool) -> ApplicationResult<Self> {
+        let command_buffer = match parser.parse() {
+            Ok(command_buffer) => command_buffer,
+            Err(diagnostic) => return Err(ApplicationError::SyntaxError(diagnostic)),
+        };
 
         Ok(Self {
             command_buffer,
             remove_targets,
+            offset: 0,
+            filters: Vec::new(),
+            container: None,
+            endianness: Endianness::Little,
         })
     }
 
-    pub fn load_subtitle<'a>(
-        file: &mut File,
+    /// Loads a script exported with [`DSCVM::write_json`].
+    pub fn load_json<R: Read>(
+        game: Game,
+        reader: &mut R,
+        remove_targets: bool,
+    ) -> ApplicationResult<Self> {
+        Ok(Self {
+            command_buffer: read_json(game, reader)?,
+            remove_targets,
+            offset: 0,
+            filters: Vec::new(),
+            container: None,
+            endianness: Endianness::Little,
+        })
+    }
+
+    /// Loads a script exported with [`DSCVM::write_csv`].
+    pub fn load_csv<R: Read>(
+        game: Game,
+        reader: &mut R,
+        remove_targets: bool,
+    ) -> ApplicationResult<Self> {
+        Ok(Self {
+            command_buffer: read_csv(game, reader)?,
+            remove_targets,
+            offset: 0,
+            filters: Vec::new(),
+            container: None,
+            endianness: Endianness::Little,
+        })
+    }
+
+    pub fn load_subtitle<R: Read>(
+        reader: &mut R,
         kind: SubtitleKind,
         pv_id: u16,
         is_english: bool,
         max_line_length: u16,
-        logger: &'a mut dyn Logger,
+        logger: &mut dyn Logger,
     ) -> ApplicationResult<Self> {
         let subtitle_file = match kind {
-            SubtitleKind::SRT => SubtitleFile::load_srt(file, logger),
-            SubtitleKind::ASS => SubtitleFile::load_ass(file, logger),
+            SubtitleKind::SRT => SubtitleFile::load_srt(reader, logger),
+            SubtitleKind::ASS => SubtitleFile::load_ass(reader, logger),
         };
 
         match subtitle_file {
@@ -151,6 +340,10 @@ impl DSCVM {
                     Ok(command_buffer) => Ok(Self {
                         command_buffer,
                         remove_targets: false,
+                        offset: 0,
+                        filters: Vec::new(),
+                        container: None,
+                        endianness: Endianness::Little,
                     }),
                     Err(err) => Err(err),
                 }
@@ -159,43 +352,347 @@ impl DSCVM {
         }
     }
 
-    pub fn dump(&self) -> String {
-        let mut output = String::new();
+    /// Groups the commands by the time they run at, ordered by time. `TIME`
+    /// commands themselves are left out.
+    pub fn events(&self) -> Vec<Event> {
+        let mut events: BTreeMap<i32, Vec<Command>> = BTreeMap::new();
+        let mut current_ts = 0;
 
         for command in &self.command_buffer {
-            output.push_str(&command.to_string());
-            output.push_str("\n");
+            if command.meta.opcode == Opcode::TIME {
+                if let Some(&time) = command.args.first() {
+                    current_ts = time;
+                }
+
+                continue;
+            }
+
+            events.entry(current_ts).or_default().push(command.clone());
+        }
+
+        events
+            .into_iter()
+            .map(|(time, commands)| Event::new(time, commands))
+            .collect()
+    }
+
+    /// Turns events back into commands, with a `TIME` command before each
+    /// event.
+    fn commands_from_events(&self, events: Vec<Event>) -> Vec<Command> {
+        let time_meta = self
+            .command_buffer
+            .iter()
+            .find(|command| command.meta.opcode == Opcode::TIME)
+            .map_or_else(|| get_time_command(0).meta, |command| command.meta);
+
+        events
+            .into_iter()
+            .flat_map(|event| {
+                std::iter::once(Command::new(time_meta, vec![event.time])).chain(event.commands)
+            })
+            .collect()
+    }
+
+    /// Returns the commands that run from `start` up to (but not including)
+    /// `end`. With `rebase`, their times are made relative to `start`;
+    /// otherwise they keep their times. Commands that set things up before
+    /// `start` are not included.
+    pub fn slice(&self, start: i32, end: i32, rebase: bool) -> Self {
+        let shift = if rebase { start } else { 0 };
+
+        let events = self
+            .events()
+            .into_iter()
+            .filter(|event| event.time >= start && event.time < end)
+            .map(|event| Event::new(event.time - shift, event.commands))
+            .collect();
+
+        Self {
+            command_buffer: self.commands_from_events(events),
+            remove_targets: self.remove_targets,
+            offset: self.offset,
+            filters: self.filters.clone(),
+            container: self.container.clone(),
+            endianness: self.endianness,
+        }
+    }
+
+    /// Returns the commands that run from `start` up to (but not including)
+    /// `end`, moved to run from `to` instead. A moved part usually lands in
+    /// the middle of the song, so it leaves its `END` and `PV_END` behind.
+    pub fn moved_section(&self, start: i32, end: i32, to: i32) -> Self {
+        let mut section = self.slice(start, end, true);
+        section.offset = section.offset.saturating_add(to);
+        section.remove_opcodes(&[Opcode::END, Opcode::PV_END]);
+
+        section
+    }
+
+    /// Copies the commands that run from `start` up to (but not including)
+    /// `end` so that they run again from `to`, e.g. to reuse the camera work
+    /// of a verse later in the song. Like [`DSCVM::moved_section`], the copy
+    /// has no `END` or `PV_END`, and the script keeps its `END` last.
+    pub fn copy_section(&mut self, start: i32, end: i32, to: i32) {
+        let copy = self.moved_section(start, end, to);
+        let end_command = self
+            .command_buffer
+            .iter()
+            .find(|command| command.meta.opcode == Opcode::END)
+            .cloned();
+
+        self.remove_opcodes(&[Opcode::END]);
+
+        let mut events = self
+            .events()
+            .into_iter()
+            .map(|event| (event.time, event.commands))
+            .collect::<BTreeMap<i32, Vec<Command>>>();
+
+        for event in copy.events() {
+            events
+                .entry(event.time.saturating_add(to))
+                .or_default()
+                .extend(event.commands);
         }
 
-        output
+        let events = events
+            .into_iter()
+            .map(|(time, commands)| Event::new(time, commands))
+            .collect();
+
+        self.command_buffer = self.commands_from_events(events);
+        self.command_buffer.extend(end_command);
+    }
+
+    /// Returns the time of the `PV_END` command, or the time of the last
+    /// command if there is none.
+    pub fn end_time(&self) -> i32 {
+        let events = self.events();
+
+        events
+            .iter()
+            .find(|event| {
+                event
+                    .commands
+                    .iter()
+                    .any(|command| command.meta.opcode == Opcode::PV_END)
+            })
+            .or(events.last())
+            .map_or(0, |event| event.time)
+    }
+
+    /// Removes all commands with the given opcodes, returning how many were
+    /// removed.
+    pub fn remove_opcodes(&mut self, opcodes: &[Opcode]) -> usize {
+        let count = self.command_buffer.len();
+
+        self.command_buffer
+            .retain(|command| !opcodes.contains(&command.meta.opcode));
+
+        count - self.command_buffer.len()
+    }
+
+    /// Returns the time of the last `TIME` command in the script.
+    pub fn duration(&self) -> i32 {
+        self.command_buffer
+            .iter()
+            .filter(|command| command.meta.opcode == Opcode::TIME)
+            .filter_map(|command| command.args.first().copied())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn dump(&self) -> String {
+        self.dump_with(&DumpOptions::default())
+    }
+
+    pub fn dump_with(&self, options: &DumpOptions) -> String {
+        format_script(&self.command_buffer, options)
+    }
+
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> ApplicationResult {
+        write_json(&self.command_buffer, writer)
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> ApplicationResult {
+        write_csv(&self.command_buffer, writer)
     }
 
-    pub fn write(&self, game: Game, file: &mut File) -> ApplicationResult {
+    pub fn write<W: Write>(&self, game: Game, writer: &mut W) -> ApplicationResult {
+        // Scripts have no delimiters between commands, so a command with the
+        // wrong number of arguments would corrupt everything after it.
+        if let Some(command) = self
+            .command_buffer
+            .iter()
+            .find(|command| command.args.len() != command.meta.param_count)
+        {
+            return Err(ApplicationError::ArgumentCountMismatch(
+                command.meta.opcode.to_string(),
+                command.meta.param_count,
+                command.args.len(),
+            ));
+        }
+
+        let mut writer = BufWriter::new(writer);
+
+        let script_length = self
+            .command_buffer
+            .iter()
+            .map(|command| (command.args.len() + 1) * 4)
+            .sum::<usize>();
+
+        let container = match game {
+            Game::F2nd | Game::X => Some(self.container.clone().unwrap_or_else(|| {
+                PVSCContainer::with_version(pvsc_version(game).unwrap_or_default())
+            })),
+            Game::F | Game::FutureTone | Game::Arcade => None,
+        };
+
         match game {
             Game::F => {
-                file.write_i32::<LE>(302121504)?;
-            }
-            Game::FutureTone => {
-                file.write_i32::<LE>(335874337)?;
+                self.endianness.write_i32(&mut writer, F_SIGNATURE)?;
             }
-            Game::F2nd | Game::X => {
-                file.write_i32::<LE>(1129535056)?;
-
-                for _ in 0..18 {
-                    file.write_i32::<LE>(0)?;
-                }
+            Game::FutureTone | Game::Arcade => {
+                self.endianness.write_i32(&mut writer, FT_SIGNATURE)?;
             }
-            _ => {}
+            Game::F2nd | Game::X => {}
         };
 
+        if let Some(container) = &container {
+            container.write_header(&mut writer, script_length, self.endianness)?;
+        }
+
         for command in &self.command_buffer {
-            file.write_i32::<LE>(command.meta.id)?;
+            self.endianness.write_i32(&mut writer, command.meta.id)?;
 
             for arg in &command.args {
-                file.write_i32::<LE>(*arg)?;
+                self.endianness.write_i32(&mut writer, *arg)?;
             }
         }
 
+        if let Some(container) = &container {
+            container.write_footer(&mut writer, script_length)?;
+        }
+
+        writer.flush()?;
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// A script that only uses opcodes F2nd and X share, with the same ids
+    /// and argument counts.
+    const SHARED_SCRIPT: &str = "
+        TIME(0);
+        MUSIC_PLAY();
+        TIME(100000);
+        TARGET(0, -1, -1, 480000, 240000, 0, 0, 500, 0, 1200, 0, 0);
+        TIME(200000);
+        PV_END();
+        END();
+    ";
+
+    fn round_trip(game: Game) -> Vec<u8> {
+        let mut source = Cursor::new(SHARED_SCRIPT);
+        let dsc_vm = DSCVM::load_plaintext(game, &mut source, false, false).unwrap();
+
+        let mut bytes = Vec::new();
+        dsc_vm.write(game, &mut bytes).unwrap();
+
+        let loaded = DSCVM::load(game, &mut Cursor::new(&bytes), false).unwrap();
+
+        let mut written = Vec::new();
+        loaded.write(game, &mut written).unwrap();
+
+        written
+    }
+
+    fn detect(bytes: &[u8]) -> Game {
+        DSCVM::detect_game(&mut Cursor::new(bytes), "test.dsc").unwrap()
+    }
+
+    #[test]
+    fn detects_round_tripped_f2nd_and_x_scripts() {
+        assert_eq!(detect(&round_trip(Game::F2nd)), Game::F2nd);
+        assert_eq!(detect(&round_trip(Game::X)), Game::X);
+    }
+
+    #[test]
+    fn unknown_version_falls_back_to_f2nd() {
+        let mut bytes = round_trip(Game::X);
+        let version = PVSCContainer::HEADER_SIZE as usize - 8;
+        bytes[version..version + 4].copy_from_slice(&0x1234_5678_i32.to_le_bytes());
+
+        assert_eq!(
+            DSCVM::possible_games(&mut Cursor::new(&bytes)).unwrap(),
+            vec![Game::F2nd, Game::X]
+        );
+        assert_eq!(detect(&bytes), Game::F2nd);
+    }
+
+    fn timeline(dsc_vm: &DSCVM) -> Vec<(i32, &'static str)> {
+        let mut time = 0;
+        let mut timeline = Vec::new();
+
+        for command in &dsc_vm.command_buffer {
+            match command.meta.opcode {
+                Opcode::TIME => time = command.args[0],
+                opcode => timeline.push((time, opcode.name())),
+            }
+        }
+
+        timeline
+    }
+
+    fn section_script() -> DSCVM {
+        let source = "
+            TIME(0);
+            MUSIC_PLAY();
+            TIME(100000);
+            CHANGE_FIELD(2);
+            TIME(150000);
+            LYRIC(1, -1);
+            TIME(200000);
+            PV_END();
+            END();
+        ";
+
+        DSCVM::load_plaintext(Game::FutureTone, &mut Cursor::new(source), false, false).unwrap()
+    }
+
+    #[test]
+    fn copy_section() {
+        let mut dsc_vm = section_script();
+        dsc_vm.copy_section(100000, 200001, 300000);
+
+        assert_eq!(
+            timeline(&dsc_vm),
+            vec![
+                (0, "MUSIC_PLAY"),
+                (100000, "CHANGE_FIELD"),
+                (150000, "LYRIC"),
+                (200000, "PV_END"),
+                (300000, "CHANGE_FIELD"),
+                (350000, "LYRIC"),
+                (350000, "END"),
+            ]
+        );
+    }
+
+    #[test]
+    fn moved_section() {
+        let section = section_script().moved_section(100000, 200001, 300000);
+
+        assert_eq!(section.offset, 300000);
+        assert_eq!(
+            timeline(&section),
+            vec![(0, "CHANGE_FIELD"), (50000, "LYRIC")]
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::sync::Arc;
 
 use crate::common::Game;
+use crate::plaintext::Diagnostic;
 
 pub type ApplicationResult<T = ()> = Result<T, ApplicationError>;
 
@@ -11,13 +12,21 @@ pub enum ApplicationError {
     FileNotFound(String),
     UnknownOpcode(i32),
     UnknownOpcodeName(String),
-    ArgumentParseError(String, String),
+    InvalidOpcodeDefinition(usize, String),
+    SyntaxError(Diagnostic),
+    ImportFailed(String),
+    ArgumentCountMismatch(String, usize, usize),
     UnsupportedGame(Game),
+    UndetectableGame(String),
+    GameMismatch(String, Game, Game),
     InvalidSubtitleFile,
     WriteFileFailed,
+    StdinReused,
+    StdoutReused,
     NoInputFiles,
     InvalidTimestamp(String),
     InvalidDifficultyString(String),
+    ValidationFailed(usize),
     IOError(Arc<std::io::Error>),
     Utf8ParseError(Arc<std::str::Utf8Error>),
 }
@@ -28,17 +37,47 @@ impl Display for ApplicationError {
             ApplicationError::FileNotFound(filename) => write!(f, "File not found: {}", filename),
             ApplicationError::UnknownOpcode(opcode) => write!(f, "Unknown opcode: {}", opcode),
             ApplicationError::UnknownOpcodeName(name) => write!(f, "Unknown opcode name: {}", name),
-            ApplicationError::ArgumentParseError(opcode, arg) => {
-                write!(f, "Invalid command argument for {}: {}", opcode, arg)
+            ApplicationError::InvalidOpcodeDefinition(line, definition) => {
+                write!(
+                    f,
+                    "Invalid opcode definition on line {}: {}",
+                    line, definition
+                )
             }
+            ApplicationError::SyntaxError(diagnostic) => write!(f, "{}", diagnostic),
+            ApplicationError::ImportFailed(message) => {
+                write!(f, "Failed to import script: {}", message)
+            }
+            ApplicationError::ArgumentCountMismatch(opcode, expected, actual) => write!(
+                f,
+                "{} takes {} argument(s), but a command has {}",
+                opcode, expected, actual
+            ),
             ApplicationError::UnsupportedGame(game) => {
-                write!(f, "Unsupported game: {}", game.to_string())
+                write!(f, "Unsupported game: {}", game)
             }
+            ApplicationError::UndetectableGame(filename) => write!(
+                f,
+                "Could not detect the game of {} (use -g to specify it)",
+                filename
+            ),
+            ApplicationError::GameMismatch(filename, expected, actual) => write!(
+                f,
+                "{} is a Project Diva {} script, but the other inputs are for Project Diva {}",
+                filename, actual, expected
+            ),
             ApplicationError::InvalidSubtitleFile => write!(f, "Invalid subtitle file"),
             ApplicationError::WriteFileFailed => write!(
                 f,
                 "Failed to write merged DSC to file (maybe missing permissions?)"
             ),
+            ApplicationError::StdinReused => {
+                write!(f, "Standard input (-) can only be used for one input.")
+            }
+            ApplicationError::StdoutReused => write!(
+                f,
+                "The output and the dump can't both be written to standard output (-)."
+            ),
             ApplicationError::NoInputFiles => write!(f, "You have not specified any input files."),
             ApplicationError::InvalidTimestamp(timestamp) => {
                 write!(f, "Invalid timestamp: {}", timestamp)
@@ -46,6 +85,9 @@ impl Display for ApplicationError {
             ApplicationError::InvalidDifficultyString(difficulty) => {
                 write!(f, "Invalid difficulty: {}", difficulty)
             }
+            ApplicationError::ValidationFailed(count) => {
+                write!(f, "Validation failed with {} problem(s)", count)
+            }
             ApplicationError::IOError(error) => write!(f, "IO error: {}", error),
             ApplicationError::Utf8ParseError(error) => write!(f, "Parse error: {}", error),
         }
@@ -0,0 +1,224 @@
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{format_timestamp, Game};
+use crate::error::{ApplicationError, ApplicationResult};
+use crate::opcodes::{Command, Opcode};
+
+/// The text formats a script can be converted to and read back from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScriptFormat {
+    /// The Open PD Script Editor syntax, see [`crate::plaintext`].
+    Plaintext,
+    Json,
+    Csv,
+}
+
+impl ScriptFormat {
+    pub fn from_string(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "text" | "txt" | "plaintext" => Some(ScriptFormat::Plaintext),
+            "json" => Some(ScriptFormat::Json),
+            "csv" => Some(ScriptFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// Picks the format from the extension of a path, falling back to
+    /// plaintext.
+    pub fn from_path(path: &str) -> Self {
+        match path.rsplit_once('.') {
+            Some((_, extension)) => match extension.to_lowercase().as_str() {
+                "json" => ScriptFormat::Json,
+                "csv" => ScriptFormat::Csv,
+                _ => ScriptFormat::Plaintext,
+            },
+            None => ScriptFormat::Plaintext,
+        }
+    }
+}
+
+/// A single command along with the time it happens at. `TIME` commands are
+/// included as well, so that a script converts back to exactly the same
+/// commands.
+#[derive(Debug, Serialize, Deserialize)]
+struct CommandRecord {
+    time: i32,
+    opcode: String,
+    #[serde(default)]
+    id: Option<i32>,
+    #[serde(default)]
+    args: Vec<i32>,
+}
+
+fn to_records(commands: &[Command]) -> Vec<CommandRecord> {
+    let mut time = 0;
+
+    commands
+        .iter()
+        .map(|command| {
+            if command.meta.opcode == Opcode::TIME {
+                if let Some(&new_time) = command.args.first() {
+                    time = new_time;
+                }
+            }
+
+            CommandRecord {
+                time,
+                opcode: command.meta.opcode.to_string(),
+                id: Some(command.meta.id),
+                args: command.args.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Turns records back into commands. The opcode is looked up by name, as
+/// ids differ between games. The time of each record is what counts, so
+/// `TIME` commands are added wherever it changes (and the argument of a
+/// `TIME` record is taken from its time), which lets records be edited or
+/// reordered by other tools.
+fn from_records(game: Game, records: Vec<CommandRecord>) -> ApplicationResult<Vec<Command>> {
+    let time_meta = match Command::get_opcode_meta_from_opcode(game, Opcode::TIME) {
+        Some(meta) => meta,
+        None => return Err(ApplicationError::UnsupportedGame(game)),
+    };
+
+    let mut commands = Vec::new();
+    let mut time = 0;
+
+    for record in records {
+        let meta = Command::get_opcode_meta_from_name(game, record.opcode)?;
+
+        if meta.opcode == Opcode::TIME {
+            commands.push(Command::new(meta, vec![record.time]));
+            time = record.time;
+            continue;
+        }
+
+        if record.time != time {
+            commands.push(Command::new(time_meta, vec![record.time]));
+            time = record.time;
+        }
+
+        if record.args.len() != meta.param_count {
+            return Err(ApplicationError::ArgumentCountMismatch(
+                meta.opcode.to_string(),
+                meta.param_count,
+                record.args.len(),
+            ));
+        }
+
+        commands.push(Command::new(meta, record.args));
+    }
+
+    Ok(commands)
+}
+
+/// Writes the commands as a JSON array of `{ time, opcode, id, args }`
+/// objects.
+pub fn write_json<W: Write>(commands: &[Command], writer: &mut W) -> ApplicationResult {
+    serde_json::to_writer_pretty(&mut *writer, &to_records(commands))
+        .map_err(std::io::Error::from)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+/// Reads commands written by [`write_json`]. Only `time` and `opcode` are
+/// required.
+pub fn read_json<R: Read>(game: Game, reader: &mut R) -> ApplicationResult<Vec<Command>> {
+    let records = serde_json::from_reader::<_, Vec<CommandRecord>>(reader)
+        .map_err(|e| ApplicationError::ImportFailed(e.to_string()))?;
+
+    from_records(game, records)
+}
+
+/// Writes the commands as a CSV table with one row per command. Besides the
+/// time in ticks, each row has the time as `MM:SS.mmm` for readability; it
+/// is ignored when reading the table back.
+pub fn write_csv<W: Write>(commands: &[Command], writer: &mut W) -> ApplicationResult {
+    let records = to_records(commands);
+    let arg_columns = records
+        .iter()
+        .map(|record| record.args.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    let mut header = ["time", "timestamp", "opcode", "id"]
+        .map(String::from)
+        .to_vec();
+    header.extend((1..=arg_columns).map(|idx| format!("arg{}", idx)));
+    csv_writer
+        .write_record(&header)
+        .map_err(std::io::Error::from)?;
+
+    for record in records {
+        let mut row = vec![
+            record.time.to_string(),
+            format_timestamp(record.time),
+            record.opcode,
+            record.id.map(|id| id.to_string()).unwrap_or_default(),
+        ];
+
+        row.extend(record.args.iter().map(|arg| arg.to_string()));
+        row.resize(header.len(), String::new());
+
+        csv_writer
+            .write_record(&row)
+            .map_err(std::io::Error::from)?;
+    }
+
+    csv_writer.flush()?;
+
+    Ok(())
+}
+
+/// Reads commands written by [`write_csv`]. Rows may have any number of
+/// argument columns; empty cells at the end of a row are ignored.
+pub fn read_csv<R: Read>(game: Game, reader: &mut R) -> ApplicationResult<Vec<Command>> {
+    let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let mut records = Vec::new();
+
+    for row in csv_reader.records() {
+        let row = row.map_err(|e| ApplicationError::ImportFailed(e.to_string()))?;
+        let line = row.position().map_or(0, |position| position.line());
+
+        let error =
+            |message: String| ApplicationError::ImportFailed(format!("line {}: {}", line, message));
+        let parse = |cell: &str| {
+            cell.trim()
+                .parse::<i32>()
+                .map_err(|_| error(format!("`{}` is not a number", cell)))
+        };
+
+        let time = parse(row.get(0).unwrap_or(""))?;
+        let opcode = match row.get(2) {
+            Some(opcode) if !opcode.trim().is_empty() => opcode.trim().to_string(),
+            _ => return Err(error("missing opcode".to_string())),
+        };
+
+        let cells = row.iter().skip(4).collect::<Vec<&str>>();
+        let used = cells
+            .iter()
+            .rposition(|cell| !cell.trim().is_empty())
+            .map_or(0, |idx| idx + 1);
+
+        let args = cells[..used]
+            .iter()
+            .map(|cell| parse(cell))
+            .collect::<ApplicationResult<Vec<i32>>>()?;
+
+        records.push(CommandRecord {
+            time,
+            opcode,
+            id: None,
+            args,
+        });
+    }
+
+    from_records(game, records)
+}
@@ -0,0 +1,240 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use crate::opcodes::{Opcode, OpcodeCategory};
+use crate::plaintext::{parse_time, parse_time_range};
+
+/// Either a single opcode, by name, or a whole [`OpcodeCategory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpcodeSelector {
+    /// The name as written, see [`OpcodeSelector::opcode_name`].
+    Opcode(String),
+    Category(OpcodeCategory),
+}
+
+impl OpcodeSelector {
+    /// The exact name of an opcode always selects that opcode, so `TARGET`
+    /// and `LYRIC` are opcodes while `target` and `lyric` are categories.
+    /// Anything else is tried as a category name (in any case), and
+    /// otherwise taken as the name of an opcode.
+    pub fn from_string(text: &str) -> Self {
+        let text = text.trim();
+
+        if Opcode::is_known_name(text) {
+            return OpcodeSelector::Opcode(text.to_string());
+        }
+
+        match OpcodeCategory::from_string(text) {
+            Some(category) => OpcodeSelector::Category(category),
+            None => OpcodeSelector::Opcode(text.to_string()),
+        }
+    }
+
+    /// The name of the selected opcode: as written if there is an opcode
+    /// with exactly that name, in upper case otherwise. This is decided
+    /// when matching, as the opcode tables from `--opcode-table` are only
+    /// loaded once the filters have been read.
+    pub fn opcode_name(&self) -> Option<Cow<'_, str>> {
+        match self {
+            OpcodeSelector::Opcode(name) if Opcode::is_known_name(name) => {
+                Some(Cow::Borrowed(name))
+            }
+            OpcodeSelector::Opcode(name) => Some(Cow::Owned(name.to_uppercase())),
+            OpcodeSelector::Category(_) => None,
+        }
+    }
+
+    pub fn matches(&self, opcode: Opcode) -> bool {
+        match self {
+            OpcodeSelector::Opcode(_) => self.opcode_name().as_deref() == Some(opcode.name()),
+            OpcodeSelector::Category(category) => opcode.category() == Some(*category),
+        }
+    }
+}
+
+impl Display for OpcodeSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpcodeSelector::Opcode(name) => write!(f, "{}", name),
+            OpcodeSelector::Category(category) => write!(f, "{}", category),
+        }
+    }
+}
+
+/// Whether an [`OpcodeFilter`] lets through only the commands it selects, or
+/// everything but them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    Keep,
+    Drop,
+}
+
+/// Decides which commands of an input make it into the merged script.
+/// Written as `keep=LIST` or `drop=LIST`, where the list holds opcode and
+/// category names separated by commas, e.g. `keep=TARGET,TARGET_FLYING_TIME`
+/// or `drop=camera,lighting`.
+///
+/// A time window can be added as `=START-END` to only filter the commands in
+/// it, e.g. `drop=targets=00:12.000-01:30.000`. Either end may be left out
+/// to filter from the start or up to the end of the script, as in
+/// `drop=targets=00:12.000-`. Times refer to the input itself, before any
+/// offset is applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcodeFilter {
+    pub kind: FilterKind,
+    pub selectors: Vec<OpcodeSelector>,
+    /// The start (inclusive) and end (exclusive) of the time window, in
+    /// ticks.
+    pub window: Option<(i32, i32)>,
+}
+
+impl OpcodeFilter {
+    pub fn from_string(text: &str) -> Option<Self> {
+        let (kind, rest) = text.split_once('=')?;
+
+        let kind = match kind.trim().to_lowercase().as_str() {
+            "keep" => FilterKind::Keep,
+            "drop" => FilterKind::Drop,
+            _ => return None,
+        };
+
+        let (list, window) = match rest.split_once('=') {
+            Some((list, window)) => (list, Some(parse_window(window)?)),
+            None => (rest, None),
+        };
+
+        Some(Self {
+            kind,
+            selectors: parse_selectors(list)?,
+            window,
+        })
+    }
+
+    /// Whether a command with the opcode at `time` passes the filter.
+    /// Commands outside of the time window always do, and so do `TIME` and
+    /// `END`, as the script can't be put together without them.
+    pub fn allows(&self, opcode: Opcode, time: i32) -> bool {
+        if opcode == Opcode::TIME || opcode == Opcode::END {
+            return true;
+        }
+
+        if let Some((start, end)) = self.window {
+            if time < start || time >= end {
+                return true;
+            }
+        }
+
+        let selected = self
+            .selectors
+            .iter()
+            .any(|selector| selector.matches(opcode));
+
+        match self.kind {
+            FilterKind::Keep => selected,
+            FilterKind::Drop => !selected,
+        }
+    }
+}
+
+fn parse_selectors(text: &str) -> Option<Vec<OpcodeSelector>> {
+    let selectors = text
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(OpcodeSelector::from_string)
+        .collect::<Vec<OpcodeSelector>>();
+
+    if selectors.is_empty() {
+        return None;
+    }
+
+    Some(selectors)
+}
+
+/// Parses `START-END`, where either end may be left out.
+fn parse_window(text: &str) -> Option<(i32, i32)> {
+    match text.trim().split_once('-')? {
+        (start, "") => Some((parse_time(start.trim())?, i32::MAX)),
+        ("", end) => parse_time_range(&format!("0-{}", end)),
+        _ => parse_time_range(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputSpec;
+    use crate::opcodes::{
+        clear_opcode_definitions, load_opcode_definitions, CUSTOM_DEFINITIONS_LOCK,
+    };
+
+    #[test]
+    fn opcode_names_win_over_categories() {
+        let filter = OpcodeFilter::from_string("keep=TARGET").unwrap();
+
+        assert_eq!(
+            filter.selectors,
+            vec![OpcodeSelector::Opcode("TARGET".to_string())]
+        );
+        assert!(filter.allows(Opcode::TARGET, 0));
+        assert!(!filter.allows(Opcode::TARGET_FLYING_TIME, 0));
+
+        assert_eq!(
+            OpcodeSelector::from_string("LYRIC"),
+            OpcodeSelector::Opcode("LYRIC".to_string())
+        );
+    }
+
+    #[test]
+    fn category_names_in_any_case() {
+        let filter = OpcodeFilter::from_string("keep=target").unwrap();
+
+        assert!(filter.allows(Opcode::TARGET, 0));
+        assert!(filter.allows(Opcode::TARGET_FLYING_TIME, 0));
+        assert!(!filter.allows(Opcode::LYRIC, 0));
+
+        assert_eq!(
+            OpcodeSelector::from_string("Camera"),
+            OpcodeSelector::Category(OpcodeCategory::Camera)
+        );
+
+        let selector = OpcodeSelector::from_string("move_camera");
+        assert_eq!(selector.opcode_name().as_deref(), Some("MOVE_CAMERA"));
+        assert!(selector.matches(Opcode::new("MOVE_CAMERA")));
+    }
+
+    #[test]
+    fn custom_opcode_names_as_written() {
+        let _lock = CUSTOM_DEFINITIONS_LOCK.lock().unwrap();
+        clear_opcode_definitions();
+        load_opcode_definitions(&mut "[ft]\n900 my_effect 1\n".as_bytes()).unwrap();
+
+        let filter = OpcodeFilter::from_string("drop=my_effect").unwrap();
+        let selector = &filter.selectors[0];
+
+        assert_eq!(selector.opcode_name().as_deref(), Some("my_effect"));
+        assert!(!filter.allows(Opcode::new("my_effect"), 0));
+        assert!(filter.allows(Opcode::new("MY_EFFECT"), 0));
+
+        clear_opcode_definitions();
+    }
+
+    #[test]
+    fn category_drop_through_input_spec() {
+        let input = InputSpec::parse("chart.dsc@drop=camera=00:10.000-");
+
+        assert_eq!(input.path, "chart.dsc");
+        assert_eq!(input.filters.len(), 1);
+
+        let filter = &input.filters[0];
+
+        assert_eq!(filter.kind, FilterKind::Drop);
+        assert_eq!(
+            filter.selectors,
+            vec![OpcodeSelector::Category(OpcodeCategory::Camera)]
+        );
+        assert!(!filter.allows(Opcode::new("MOVE_CAMERA"), 1000000));
+        assert!(!filter.allows(Opcode::new("SET_CAMERA"), 1000000));
+        assert!(filter.allows(Opcode::new("MOVE_CAMERA"), 0));
+        assert!(filter.allows(Opcode::TARGET, 1000000));
+    }
+}
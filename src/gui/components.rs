@@ -1,9 +1,9 @@
 use std::path::Path;
 
-use imgui::{ComboBox, Condition, ListBox, PopupModal, Selectable, Ui, Window};
+use imgui::{ComboBox, Condition, ListBox, PopupModal, Selectable, TreeNodeFlags, Ui, Window};
 use rfd::FileDialog;
 
-use crate::common::GAME_MAP;
+use dsc_merger::{OpcodeCategory, GAME_MAP};
 
 use super::{state::GUIState, utils::rgba_to_imvec};
 
@@ -33,6 +33,7 @@ impl GUIComponents {
                 self.draw_input_columns(ui, state);
                 self.draw_subtitle_components(ui, state);
                 self.draw_remove_targets_components(ui, state);
+                self.draw_drop_categories_components(ui, state);
                 self.draw_vertical_spacing(ui, 10.0);
                 self.draw_challenge_time_components(ui, state);
                 self.draw_vertical_spacing(ui, 10.0);
@@ -44,6 +45,7 @@ impl GUIComponents {
                 self.draw_status_bar(ui, state);
 
                 self.draw_remove_targets_dialog(ui, state);
+                self.draw_drop_categories_dialog(ui, state);
                 self.draw_success_dialog(ui, state);
                 self.draw_error_dialog(ui, state);
                 self.draw_lyrics_dialog(ui, state);
@@ -55,7 +57,8 @@ impl GUIComponents {
     }
 
     fn draw_game_selection_combo_box(&mut self, ui: &Ui, state: &mut GUIState) {
-        let items = GAME_MAP.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let mut items = vec!["Detect from input files"];
+        items.extend(GAME_MAP.iter().map(|(name, _)| *name));
 
         self.draw_left_label(ui, "Target game:");
 
@@ -260,6 +263,20 @@ impl GUIComponents {
         }
     }
 
+    fn draw_drop_categories_components(&mut self, ui: &Ui, state: &mut GUIState) {
+        if state.dsc_inputs.len() == 0 && state.plaintext_inputs.len() == 0 {
+            return;
+        }
+
+        self.draw_vertical_spacing(ui, 5.0);
+
+        self.draw_left_label(ui, "Leave out commands from scripts:");
+
+        if ui.button("Select categories...") {
+            state.show_drop_categories_dialog = true;
+        }
+    }
+
     fn draw_has_challenge_time_checkbox(&mut self, ui: &Ui, state: &mut GUIState) {
         ui.checkbox(
             "This chart has Challenge Time",
@@ -590,6 +607,41 @@ impl GUIComponents {
         });
     }
 
+    fn draw_drop_categories_dialog(&mut self, ui: &Ui, state: &mut GUIState) {
+        if !state.show_drop_categories_dialog {
+            return;
+        }
+
+        let window = Window::new("Leave out commands...")
+            .always_auto_resize(true)
+            .resizable(false)
+            .collapsible(false);
+
+        window.build(ui, || {
+            ui.text("Select the kinds of commands to leave out of each file:");
+
+            self.draw_vertical_spacing(ui, 5.0);
+
+            for (file, dropped) in state.drop_categories_map.iter_mut() {
+                let id = ui.push_id(file.as_str());
+
+                if ui.collapsing_header(self.filename_from_path(file), TreeNodeFlags::empty()) {
+                    for (category, drop) in OpcodeCategory::ALL.iter().zip(dropped.iter_mut()) {
+                        ui.checkbox(category.description(), drop);
+                    }
+                }
+
+                id.pop();
+            }
+
+            self.draw_vertical_spacing(ui, 5.0);
+
+            if self.draw_centered_button(ui, "Close") {
+                state.show_drop_categories_dialog = false;
+            }
+        });
+    }
+
     fn draw_centered_button(&mut self, ui: &Ui, text: &str) -> bool {
         let w = text.len() as f32 * 16.0 + 20.0;
         let x = (ui.content_region_avail()[0] - w) / 2.0;
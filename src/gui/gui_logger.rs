@@ -1,4 +1,4 @@
-use crate::logger::Logger;
+use dsc_merger::Logger;
 
 pub struct GUILogger {
     pub log: String,
@@ -1,9 +1,8 @@
 use imgui::FontId;
 
-use crate::{
-    application::Application,
-    common::{ChallengeTime, ChallengeTimeDifficulty, Game, GAME_MAP},
-    error::ApplicationResult,
+use dsc_merger::{
+    Application, ApplicationOptions, ApplicationResult, ChallengeTime, ChallengeTimeDifficulty,
+    Game, OpcodeCategory, GAME_MAP,
 };
 
 use super::gui_logger::GUILogger;
@@ -18,9 +17,12 @@ pub struct GUIState {
     pub plaintext_inputs: Vec<String>,
     pub subtitle_inputs: Vec<String>,
     pub remove_targets_map: Vec<(String, bool)>,
+    /// The categories of commands to leave out of each script, in the order
+    /// of `OpcodeCategory::ALL`.
+    pub drop_categories_map: Vec<(String, [bool; OpcodeCategory::ALL.len()])>,
 
     pub output: String,
-    game: Game,
+    game: Option<Game>,
 
     pub selected_game_index: usize,
 
@@ -41,6 +43,7 @@ pub struct GUIState {
 
     pub show_lyrics_dialog: bool,
     pub show_remove_targets_dialog: bool,
+    pub show_drop_categories_dialog: bool,
 
     pub show_success_dialog: bool,
     pub show_error_dialog: bool,
@@ -59,8 +62,9 @@ impl GUIState {
             plaintext_inputs: Vec::new(),
             subtitle_inputs: Vec::new(),
             remove_targets_map: Vec::new(),
+            drop_categories_map: Vec::new(),
             output: String::new(),
-            game: Game::FutureTone,
+            game: None,
 
             selected_game_index: 0,
 
@@ -81,6 +85,7 @@ impl GUIState {
 
             show_lyrics_dialog: false,
             show_remove_targets_dialog: false,
+            show_drop_categories_dialog: false,
 
             show_success_dialog: false,
             show_error_dialog: false,
@@ -93,8 +98,9 @@ impl GUIState {
         self.plaintext_inputs.clear();
         self.subtitle_inputs.clear();
         self.remove_targets_map.clear();
+        self.drop_categories_map.clear();
         self.output.clear();
-        self.game = Game::FutureTone;
+        self.game = None;
 
         self.selected_game_index = 0;
 
@@ -117,6 +123,7 @@ impl GUIState {
 
         self.show_lyrics_dialog = false;
         self.show_remove_targets_dialog = false;
+        self.show_drop_categories_dialog = false;
 
         self.show_success_dialog = false;
         self.show_error_dialog = false;
@@ -129,7 +136,9 @@ impl GUIState {
         }
 
         self.dsc_inputs.push(input.clone());
-        self.remove_targets_map.push((input, false));
+        self.remove_targets_map.push((input.clone(), false));
+        self.drop_categories_map
+            .push((input, [false; OpcodeCategory::ALL.len()]));
     }
 
     pub fn add_plaintext_input(&mut self, input: String) {
@@ -138,7 +147,9 @@ impl GUIState {
         }
 
         self.plaintext_inputs.push(input.clone());
-        self.remove_targets_map.push((input, false));
+        self.remove_targets_map.push((input.clone(), false));
+        self.drop_categories_map
+            .push((input, [false; OpcodeCategory::ALL.len()]));
     }
 
     pub fn add_subtitle_input(&mut self, input: String) {
@@ -149,12 +160,16 @@ impl GUIState {
         let removed = self.dsc_inputs.remove(index);
         self.remove_targets_map
             .retain(|(input, _)| input != &removed);
+        self.drop_categories_map
+            .retain(|(input, _)| input != &removed);
     }
 
     pub fn remove_plaintext_input(&mut self, index: usize) {
         let removed = self.plaintext_inputs.remove(index);
         self.remove_targets_map
             .retain(|(input, _)| input != &removed);
+        self.drop_categories_map
+            .retain(|(input, _)| input != &removed);
     }
 
     pub fn remove_subtitle_input(&mut self, index: usize) {
@@ -166,11 +181,17 @@ impl GUIState {
     }
 
     pub fn set_game(&mut self, index: usize) {
+        // The first item of the game selection combo box is auto-detection.
+        if index == 0 {
+            self.game = None;
+            return;
+        }
+
         if index > GAME_MAP.len() {
             return;
         }
 
-        self.game = GAME_MAP[index].1;
+        self.game = Some(GAME_MAP[index - 1].1);
     }
 
     pub fn set_difficulty(&mut self, index: usize) {
@@ -189,6 +210,30 @@ impl GUIState {
         remove_targets
     }
 
+    /// Adds a `@drop=` suffix to an input path for the categories that were
+    /// selected for it.
+    fn with_dropped_categories(&self, input: &str) -> String {
+        let dropped = self
+            .drop_categories_map
+            .iter()
+            .find(|(path, _)| path == input)
+            .map(|(_, dropped)| {
+                OpcodeCategory::ALL
+                    .iter()
+                    .zip(dropped)
+                    .filter(|(_, drop)| **drop)
+                    .map(|(category, _)| category.name())
+                    .collect::<Vec<&str>>()
+            })
+            .unwrap_or_default();
+
+        if dropped.is_empty() {
+            return input.to_string();
+        }
+
+        format!("{}@drop={}", input, dropped.join(","))
+    }
+
     pub fn merge(&mut self) -> ApplicationResult {
         let mut challenge_time: Option<ChallengeTime> = None;
 
@@ -207,21 +252,30 @@ impl GUIState {
             challenge_time = Some(ct.unwrap());
         }
 
-        let mut application = Application::new(
-            self.dsc_inputs.clone(),
-            self.plaintext_inputs.clone(),
-            self.subtitle_inputs.clone(),
-            self.get_remove_targets_inputs(),
-            self.output.clone(),
-            self.game,
-            self.pv_id.clamp(0, 999).try_into().unwrap(),
-            self.english_lyrics,
-            self.max_lyric_length.clamp(0, 1000).try_into().unwrap(),
-            false,
-            true,
+        let options = ApplicationOptions {
+            dsc_inputs: self
+                .dsc_inputs
+                .iter()
+                .map(|input| self.with_dropped_categories(input))
+                .collect(),
+            plaintext_inputs: self
+                .plaintext_inputs
+                .iter()
+                .map(|input| self.with_dropped_categories(input))
+                .collect(),
+            subtitle_inputs: self.subtitle_inputs.clone(),
+            remove_targets_inputs: self.get_remove_targets_inputs(),
+            output: self.output.clone(),
+            game: self.game,
+            pv_id: self.pv_id.clamp(0, 999).try_into().unwrap(),
+            english_lyrics: self.english_lyrics,
+            max_lyric_length: self.max_lyric_length.clamp(0, 1000).try_into().unwrap(),
+            verbose: true,
             challenge_time,
-            &mut self.logger,
-        );
+            ..Default::default()
+        };
+
+        let mut application = Application::new(options, &mut self.logger);
 
         application.run()
     }
@@ -0,0 +1,159 @@
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::sync::OnceLock;
+
+use crate::common::Game;
+use crate::dsc::DSCVM;
+use crate::error::{ApplicationError, ApplicationResult};
+use crate::filter::OpcodeFilter;
+use crate::plaintext::{parse_offset, parse_time, parse_time_range};
+
+/// The path that stands for standard input or standard output.
+pub const STDIO_PATH: &str = "-";
+
+static STDIN: OnceLock<Vec<u8>> = OnceLock::new();
+
+pub trait InputReader: Read + Seek {}
+
+impl<T: Read + Seek> InputReader for T {}
+
+/// Returns everything on standard input. It is read in full the first time,
+/// so that it can be seeked and read again like a file.
+pub fn stdin_contents() -> ApplicationResult<&'static [u8]> {
+    if let Some(contents) = STDIN.get() {
+        return Ok(contents);
+    }
+
+    let mut contents = Vec::new();
+    std::io::stdin().read_to_end(&mut contents)?;
+
+    Ok(STDIN.get_or_init(|| contents))
+}
+
+/// Opens an input file, or standard input for `-`.
+pub fn open_input(path: &str) -> ApplicationResult<Box<dyn InputReader>> {
+    if path == STDIO_PATH {
+        return Ok(Box::new(Cursor::new(stdin_contents()?)));
+    }
+
+    match File::open(path) {
+        Ok(file) => Ok(Box::new(BufReader::new(file))),
+        Err(_) => Err(ApplicationError::FileNotFound(path.to_owned())),
+    }
+}
+
+/// Creates an output file, or writes to standard output for `-`.
+pub fn open_output(path: &str) -> ApplicationResult<Box<dyn Write>> {
+    if path == STDIO_PATH {
+        return Ok(Box::new(std::io::stdout()));
+    }
+
+    match File::create(path) {
+        Ok(file) => Ok(Box::new(file)),
+        Err(_) => Err(ApplicationError::WriteFileFailed),
+    }
+}
+
+/// An input path as given on the command line. Paths may be followed by
+/// `@`-separated modifiers that only apply to that input, for example
+/// `camera.dsc@f2nd` to load a script made for F2nd, or `chart.dsc@+250ms`
+/// to move all of its commands 250 ms later. See [`Section`] for using only
+/// part of an input and [`OpcodeFilter`] for using only some of its
+/// commands. Anything after an `@` that isn't a recognized modifier is
+/// treated as part of the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSpec {
+    pub path: String,
+    pub game: Option<Game>,
+    /// Offset in ticks.
+    pub offset: i32,
+    /// Time to move the input to, given as `=TIME`.
+    pub start: Option<i32>,
+    pub section: Option<Section>,
+    pub filters: Vec<OpcodeFilter>,
+}
+
+/// A part of an input to use instead of all of it, written as `START-END`
+/// (e.g. `chart.dsc@00:45.000-01:15.000`). The commands keep their times,
+/// unless `=TIME` is added at the end to move the part to start at that
+/// time (`=0` to start at the beginning of the script).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+    pub start: i32,
+    pub end: i32,
+    pub to: Option<i32>,
+}
+
+impl Section {
+    pub fn from_string(text: &str) -> Option<Self> {
+        let (range, to) = match text.split_once('=') {
+            Some((range, to)) => (range, Some(parse_time(to.trim())?)),
+            None => (text, None),
+        };
+
+        let (start, end) = parse_time_range(range)?;
+
+        Some(Self { start, end, to })
+    }
+
+    /// Cuts the part out of a script, moving it if needed (see
+    /// [`DSCVM::moved_section`]).
+    pub fn apply(&self, dsc_vm: &DSCVM) -> DSCVM {
+        match self.to {
+            Some(to) => dsc_vm.moved_section(self.start, self.end, to),
+            None => dsc_vm.slice(self.start, self.end, false),
+        }
+    }
+}
+
+impl InputSpec {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            game: None,
+            offset: 0,
+            start: None,
+            section: None,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn parse(spec: &str) -> Self {
+        let mut input = InputSpec::new(spec.to_string());
+
+        while let Some((rest, modifier)) = input.path.rsplit_once('@') {
+            let is_offset = modifier.starts_with('+') || modifier.starts_with('-');
+            let is_filter = modifier.starts_with("keep=") || modifier.starts_with("drop=");
+
+            match Game::from_string(modifier) {
+                Some(game) => {
+                    input.game.get_or_insert(game);
+                }
+                None if modifier.starts_with('=') => match parse_time(&modifier[1..]) {
+                    Some(start) => {
+                        input.start.get_or_insert(start);
+                    }
+                    None => break,
+                },
+                None if is_filter => match OpcodeFilter::from_string(modifier) {
+                    Some(filter) => input.filters.insert(0, filter),
+                    None => break,
+                },
+                None if is_offset => match parse_offset(modifier) {
+                    Some(offset) => input.offset = input.offset.saturating_add(offset),
+                    None => break,
+                },
+                None => match Section::from_string(modifier) {
+                    Some(section) => {
+                        input.section.get_or_insert(section);
+                    }
+                    None => break,
+                },
+            }
+
+            input.path = rest.to_string();
+        }
+
+        input
+    }
+}
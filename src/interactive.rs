@@ -1,9 +1,8 @@
 use requestty::{prompt_one, Question};
 
-use crate::{
-    application::Application,
-    common::{ChallengeTime, ChallengeTimeDifficulty, Game, GAME_MAP},
-    logger::simple_logger::SimpleLogger,
+use dsc_merger::{
+    Application, ApplicationOptions, ChallengeTime, ChallengeTimeDifficulty, Game, SimpleLogger,
+    GAME_MAP,
 };
 
 struct InputFiles {
@@ -56,7 +55,7 @@ impl InteractiveTUI {
         let mut english_lyrics = false;
         let mut max_lyric_length = 75;
 
-        if input_files.subtitle.len() > 0 {
+        if !input_files.subtitle.is_empty() {
             println!("It looks like you're adding lyrics. To make things easier, tell me a few things about your song!");
 
             pv_id = tui.prompt_pv_id();
@@ -86,21 +85,22 @@ impl InteractiveTUI {
 
         let mut logger = SimpleLogger::new();
 
-        let mut application = Application::new(
-            input_files.dsc,
-            input_files.plaintext,
-            input_files.subtitle,
-            remove_targets_files,
+        let options = ApplicationOptions {
+            dsc_inputs: input_files.dsc,
+            plaintext_inputs: input_files.plaintext,
+            subtitle_inputs: input_files.subtitle,
+            remove_targets_inputs: remove_targets_files,
             output,
             game,
             pv_id,
             english_lyrics,
             max_lyric_length,
-            false,
             verbose,
             challenge_time,
-            &mut logger,
-        );
+            ..Default::default()
+        };
+
+        let mut application = Application::new(options, &mut logger);
 
         match application.run() {
             Ok(_) => println!("Done!"),
@@ -110,9 +110,10 @@ impl InteractiveTUI {
         tui.display_press_enter_to_exit();
     }
 
-    fn prompt_game(&self) -> Game {
+    fn prompt_game(&self) -> Option<Game> {
         let question = Question::select("game")
             .message("Select the game the chart is designed for.")
+            .choice("Detect from input files")
             .choices(GAME_MAP.map(|(name, _)| name))
             .default_separator()
             .choice("Abort")
@@ -126,8 +127,9 @@ impl InteractiveTUI {
                     println!("Aborted.");
                     std::process::exit(0);
                 }
+                "Detect from input files" => None,
                 _ => match GAME_MAP.iter().find(|(name, _)| name == &item.text) {
-                    Some((_, game)) => *game,
+                    Some((_, game)) => Some(*game),
                     None => {
                         println!("Error: Invalid game.");
                         std::process::exit(1);
@@ -158,21 +160,20 @@ impl InteractiveTUI {
 
             match answer.as_list_item() {
                 Some(item) => match item.index {
-                    0 | 1 | 2 => {
+                    0..=2 => {
                         let question = Question::input("path")
                             .message("Enter the path to the file:")
                             .build();
 
                         let answer = prompt_one(question).unwrap();
 
-                        match answer.as_string() {
-                            Some(input) => match item.index {
+                        if let Some(input) = answer.as_string() {
+                            match item.index {
                                 0 => input_files.add_dsc(input.to_string()),
                                 1 => input_files.add_plaintext(input.to_string()),
                                 2 => input_files.add_subtitle(input.to_string()),
                                 _ => {}
-                            },
-                            None => {}
+                            }
                         }
                     }
                     3 => {
@@ -297,10 +298,7 @@ impl InteractiveTUI {
                     println!("Aborted.");
                     std::process::exit(0);
                 }
-                idx => {
-                    let difficulty = ChallengeTimeDifficulty::from_integer(idx).unwrap();
-                    difficulty
-                }
+                idx => ChallengeTimeDifficulty::from_integer(idx).unwrap(),
             },
             None => std::process::exit(-1),
         }
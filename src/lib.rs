@@ -0,0 +1,45 @@
+//! Merging utility for Project Diva DSC script files.
+//!
+//! The library exposes everything needed to load scripts (binary, plaintext,
+//! JSON, CSV and subtitle files), merge them, apply transformations such as
+//! target removal or Challenge Time, and write them back out. The command line,
+//! interactive and GUI front-ends are thin layers on top of [`Application`].
+
+pub mod application;
+pub mod common;
+pub mod container;
+pub mod converter;
+pub mod diff;
+pub mod dsc;
+pub mod error;
+pub mod export;
+pub mod filter;
+pub mod input;
+pub mod logger;
+pub mod merger;
+pub mod opcodes;
+pub mod plaintext;
+pub mod retime;
+pub mod subtitle;
+pub mod validator;
+
+pub use application::{Application, ApplicationOptions};
+pub use common::{ChallengeTime, ChallengeTimeDifficulty, Endianness, Game, GAME_MAP};
+pub use container::PVSCContainer;
+pub use converter::DSCConverter;
+pub use diff::{diff, DiffEntry};
+pub use dsc::DSCVM;
+pub use error::{ApplicationError, ApplicationResult};
+pub use export::ScriptFormat;
+pub use filter::{FilterKind, OpcodeFilter, OpcodeSelector};
+pub use input::{open_input, open_output, InputSpec, Section, STDIO_PATH};
+pub use logger::{simple_logger::SimpleLogger, Logger};
+pub use merger::{DSCMerger, Event};
+pub use opcodes::{
+    clear_opcode_definitions, load_opcode_definitions, Command, Opcode, OpcodeCategory, OpcodeMeta,
+    OpcodeParam, ParamKind, TargetDefaults,
+};
+pub use plaintext::{Diagnostic, DumpOptions, PlaintextParser, TimeFormat};
+pub use retime::RetimeEdit;
+pub use subtitle::{SubtitleFile, SubtitleKind};
+pub use validator::{validate, ValidationIssue};
@@ -2,6 +2,12 @@ use super::Logger;
 
 pub struct SimpleLogger;
 
+impl Default for SimpleLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SimpleLogger {
     pub fn new() -> Self {
         Self {}
@@ -10,15 +16,15 @@ impl SimpleLogger {
 
 impl Logger for SimpleLogger {
     fn log(&mut self, message: String) {
-        println!("{}", message);
+        eprintln!("{}", message);
     }
 
     fn log_lyrics_line(&mut self, line: String) {
-        println!("{}", line);
+        eprintln!("{}", line);
     }
 
     fn log_problematic_lyrics_line(&mut self, line: i32, expected: u16, actual: usize) {
-        println!(
+        eprintln!(
             "\x1b[33mWarning: Line {} exceeds recommended byte length of {}. Actual length: {}\x1b[39m",
             line, expected, actual
         );
@@ -1,36 +1,68 @@
-#![allow(uncommon_codepoints)]
-
 use std::env;
+use std::process::ExitCode;
 
-use clap::Parser;
-use common::{ChallengeTime, ChallengeTimeDifficulty, Game};
-use error::ApplicationResult;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use dsc_merger::plaintext;
+use dsc_merger::{
+    Application, ApplicationError, ApplicationOptions, ApplicationResult, ChallengeTime,
+    ChallengeTimeDifficulty, DumpOptions, Endianness, Game, OpcodeFilter, RetimeEdit, ScriptFormat,
+    SimpleLogger, TimeFormat,
+};
 
 #[cfg(feature = "gui")]
 use gui::GUI;
 
 #[cfg(not(feature = "gui"))]
 use interactive::InteractiveTUI;
-use logger::simple_logger::SimpleLogger;
-
-use crate::application::Application;
 
-mod application;
-mod common;
-mod dsc;
-mod error;
+mod commands;
 #[cfg(feature = "gui")]
 mod gui;
 #[cfg(not(feature = "gui"))]
 mod interactive;
-mod logger;
-mod merger;
-mod opcodes;
-mod subtitle;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
 struct Arguments {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    // Merging is the default when no subcommand is given
+    #[command(flatten)]
+    merge: MergeArguments,
+}
+
+// Only ever constructed once, so the size of the merge arguments doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Merge scripts into one (default)
+    Merge(MergeArguments),
+
+    /// Convert an encoded script to plaintext
+    Dump(DumpArguments),
+
+    /// Convert a plaintext script to an encoded one
+    Compile(CompileArguments),
+
+    /// Show information about encoded scripts
+    Info(InfoArguments),
+
+    /// Check scripts for problems
+    Validate(ValidateArguments),
+
+    /// Show the differences between two encoded scripts
+    Diff(DiffArguments),
+}
+
+#[derive(Args, Debug)]
+struct MergeArguments {
     // Input paths
     #[arg(long, short)]
     input: Vec<String>,
@@ -44,13 +76,55 @@ struct Arguments {
     #[arg(long = "rt")]
     remove_targets_input: Vec<String>,
 
+    // Opcodes to keep or drop from the -i/-p input given right before
+    #[arg(long)]
+    keep: Vec<String>,
+
+    #[arg(long)]
+    drop: Vec<String>,
+
+    // Extra opcode definitions (e.g. for modded games)
+    #[arg(long)]
+    opcode_table: Vec<String>,
+
+    // Fill in missing plaintext arguments with defaults instead of failing
+    #[arg(long)]
+    pad_missing_args: bool,
+
+    // Time to move every input by, e.g. +250ms or -1.5s
+    #[arg(long, allow_hyphen_values = true)]
+    offset: Option<String>,
+
+    // Song edits: insert time (DURATION@TIME) or remove a range (START-END)
+    #[arg(long)]
+    insert: Vec<String>,
+
+    #[arg(long)]
+    remove: Vec<String>,
+
+    // Play the inputs one after another instead of at the same time
+    #[arg(long)]
+    medley: bool,
+
+    // Where each -i and then each -p input was given, filled in from the matches
+    #[arg(skip)]
+    input_order: Vec<usize>,
+
     // Output path
     #[arg(short, long, default_value = "output.dsc")]
     output: String,
 
-    // Game name
-    #[arg(long, short, default_value = "FT")]
-    game: String,
+    // Game name (detected from the input files if omitted)
+    #[arg(long, short)]
+    game: Option<String>,
+
+    // Game to convert the output to
+    #[arg(long)]
+    convert_to: Option<String>,
+
+    // Byte order of the output (defaults to the byte order of the inputs)
+    #[arg(long)]
+    endianness: Option<String>,
 
     // Lyrics-related arguments
     #[arg(long, default_value = "0")]
@@ -76,18 +150,121 @@ struct Arguments {
     #[arg(long)]
     dump: bool,
 
+    // Write the dump to a file instead of printing it (implies --dump)
+    #[arg(long)]
+    dump_output: Option<String>,
+
     #[arg(long, short)]
     verbose: bool,
 }
 
-fn get_challenge_time_object(args: &Arguments) -> ApplicationResult<Option<ChallengeTime>> {
+// Options shared by the subcommands that work on individual scripts
+#[derive(Args, Debug)]
+struct ScriptArguments {
+    // Game name (detected from the input files if omitted, Future Tone for
+    // plaintext input)
+    #[arg(long, short)]
+    game: Option<String>,
+
+    // Extra opcode definitions (e.g. for modded games)
+    #[arg(long)]
+    opcode_table: Vec<String>,
+}
+
+impl ScriptArguments {
+    fn prepare(&self) -> Result<Option<Game>, String> {
+        commands::load_opcode_tables(&self.opcode_table).map_err(|e| format!("Error: {}", e))?;
+        parse_game(&self.game)
+    }
+}
+
+#[derive(Args, Debug)]
+struct DumpArguments {
+    input: String,
+
+    // Output path (prints to the terminal if omitted)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    // Output format: text, json or csv (guessed from the output path if omitted)
+    #[arg(long)]
+    format: Option<String>,
+
+    // How to write times: ticks, comment or inline
+    #[arg(long)]
+    time_format: Option<String>,
+
+    // Name the arguments of each command and add its time as a comment
+    #[arg(long)]
+    annotate: bool,
+
+    // Indent commands under the TIME they belong to
+    #[arg(long)]
+    group: bool,
+
+    #[command(flatten)]
+    script: ScriptArguments,
+}
+
+#[derive(Args, Debug)]
+struct CompileArguments {
+    input: String,
+
+    #[arg(short, long, default_value = "output.dsc")]
+    output: String,
+
+    #[arg(long)]
+    endianness: Option<String>,
+
+    #[arg(long)]
+    pad_missing_args: bool,
+
+    #[command(flatten)]
+    script: ScriptArguments,
+}
+
+#[derive(Args, Debug)]
+struct InfoArguments {
+    #[arg(required = true)]
+    input: Vec<String>,
+
+    #[command(flatten)]
+    script: ScriptArguments,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArguments {
+    #[arg(required = true)]
+    input: Vec<String>,
+
+    // Treat the inputs as plaintext scripts
+    #[arg(long, short)]
+    plaintext: bool,
+
+    #[arg(long)]
+    pad_missing_args: bool,
+
+    #[command(flatten)]
+    script: ScriptArguments,
+}
+
+#[derive(Args, Debug)]
+struct DiffArguments {
+    old: String,
+    new: String,
+
+    #[command(flatten)]
+    script: ScriptArguments,
+}
+
+fn get_challenge_time_object(args: &MergeArguments) -> ApplicationResult<Option<ChallengeTime>> {
     if args.ct_start.is_some() && args.ct_end.is_some() && args.difficulty.is_some() {
         let fallback = "".to_string();
 
         let start_str = args.ct_start.as_ref().unwrap_or(&fallback);
         let end_str = args.ct_end.as_ref().unwrap_or(&fallback);
         let difficulty_str = args.difficulty.as_ref().unwrap_or(&fallback);
-        let difficulty = ChallengeTimeDifficulty::from_string(&difficulty_str);
+        let difficulty = ChallengeTimeDifficulty::from_string(difficulty_str);
 
         match difficulty {
             Some(difficulty) => {
@@ -99,7 +276,7 @@ fn get_challenge_time_object(args: &Arguments) -> ApplicationResult<Option<Chall
                     Err(e) => Err(e),
                 }
             }
-            None => Err(error::ApplicationError::InvalidDifficultyString(
+            None => Err(ApplicationError::InvalidDifficultyString(
                 difficulty_str.to_string(),
             )),
         }
@@ -108,65 +285,258 @@ fn get_challenge_time_object(args: &Arguments) -> ApplicationResult<Option<Chall
     }
 }
 
-fn main() {
-    let argc = env::args().len();
-
-    if argc == 1 {
-        // User probably double-clicked the exe
-        #[cfg(feature = "gui")]
-        GUI::new().run();
+fn parse_game(game: &Option<String>) -> Result<Option<Game>, String> {
+    match game {
+        Some(game_str) => match Game::from_string(game_str) {
+            Some(game) => Ok(Some(game)),
+            None => Err(format!("Invalid game: {}", game_str)),
+        },
+        None => Ok(None),
+    }
+}
 
-        #[cfg(not(feature = "gui"))]
-        InteractiveTUI::start();
+fn parse_offset(offset: &Option<String>) -> Result<i32, String> {
+    match offset {
+        Some(offset_str) => match plaintext::parse_offset(offset_str) {
+            Some(offset) => Ok(offset),
+            None => Err(format!("Invalid offset: {}", offset_str)),
+        },
+        None => Ok(0),
+    }
+}
 
-        return;
+fn parse_retime_edits(args: &MergeArguments) -> Result<Vec<RetimeEdit>, String> {
+    let mut edits = Vec::new();
+
+    for insert in &args.insert {
+        match RetimeEdit::parse_insert(insert) {
+            Some(edit) => edits.push(edit),
+            None => {
+                return Err(format!(
+                    "Invalid insert (expected DURATION@TIME): {}",
+                    insert
+                ))
+            }
+        }
     }
 
-    let args = Arguments::parse();
-
-    let game = match args.game.to_lowercase().as_str() {
-        "f" => Game::F,
-        "f2" | "f2nd" | "f 2nd" => Game::F2nd,
-        "x" => Game::X,
-        "ft" | "futuretone" | "future tone" => Game::FutureTone,
-        "arcade" | "aft" => Game::Arcade,
-        _ => {
-            println!("Invalid game: {}", args.game);
-            return;
+    for remove in &args.remove {
+        match RetimeEdit::parse_remove(remove) {
+            Some(edit) => edits.push(edit),
+            None => return Err(format!("Invalid removal (expected START-END): {}", remove)),
         }
+    }
+
+    Ok(edits)
+}
+
+/// Turns each `--keep` and `--drop` into an `@keep=`/`@drop=` modifier of
+/// the `-i` or `-p` input that comes right before it on the command line.
+fn attach_filters(args: &mut MergeArguments, matches: &ArgMatches) -> Result<(), String> {
+    let positions = |id: &str| -> Vec<usize> {
+        matches
+            .indices_of(id)
+            .map(|indices| indices.collect())
+            .unwrap_or_default()
     };
 
-    let challenge_time = get_challenge_time_object(&args);
+    let input_positions = positions("input");
+    let plaintext_positions = positions("plaintext_input");
+
+    for (id, values) in [("keep", &args.keep), ("drop", &args.drop)] {
+        for (position, names) in positions(id).into_iter().zip(values) {
+            // The last input of a kind before the filter, as (position, index)
+            let last_before = |positions: &[usize]| {
+                let count = positions.partition_point(|&input_position| input_position < position);
+                count.checked_sub(1).map(|idx| (positions[idx], idx))
+            };
+
+            let input = match (
+                last_before(&input_positions),
+                last_before(&plaintext_positions),
+            ) {
+                (Some(dsc), Some(plaintext)) if plaintext > dsc => {
+                    &mut args.plaintext_input[plaintext.1]
+                }
+                (Some((_, idx)), _) => &mut args.input[idx],
+                (None, Some((_, idx))) => &mut args.plaintext_input[idx],
+                (None, None) => {
+                    return Err(format!(
+                        "--{} {} has to come after the -i or -p input it applies to",
+                        id, names
+                    ))
+                }
+            };
+
+            let modifier = format!("{}={}", id, names);
 
-    if challenge_time.is_err() {
-        println!("{}", challenge_time.unwrap_err());
-        return;
+            if OpcodeFilter::from_string(&modifier).is_none() {
+                return Err(format!("Invalid filter: --{} {}", id, names));
+            }
+
+            input.push_str(&format!("@{}", modifier));
+        }
     }
 
+    Ok(())
+}
+
+/// The command line positions of the -i inputs followed by those of the -p
+/// inputs, so a medley can play them in the order they were given.
+fn input_order(matches: &ArgMatches) -> Vec<usize> {
+    ["input", "plaintext_input"]
+        .into_iter()
+        .flat_map(|id| matches.indices_of(id).into_iter().flatten())
+        .collect()
+}
+
+fn parse_endianness(endianness: &Option<String>) -> Result<Option<Endianness>, String> {
+    match endianness {
+        Some(endianness_str) => match Endianness::from_string(endianness_str) {
+            Some(endianness) => Ok(Some(endianness)),
+            None => Err(format!("Invalid endianness: {}", endianness_str)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn merge(args: MergeArguments) -> Result<(), String> {
+    let game = parse_game(&args.game)?;
+    let convert_to = parse_game(&args.convert_to)?;
+    let endianness = parse_endianness(&args.endianness)?;
+    let offset = parse_offset(&args.offset)?;
+    let retime_edits = parse_retime_edits(&args)?;
+    let challenge_time = get_challenge_time_object(&args).map_err(|e| e.to_string())?;
+
     let mut logger = SimpleLogger::new();
 
-    let mut application = Application::new(
-        args.input,
-        args.plaintext_input,
-        args.subtitle_input,
-        args.remove_targets_input,
-        args.output,
+    let options = ApplicationOptions {
+        dsc_inputs: args.input,
+        plaintext_inputs: args.plaintext_input,
+        subtitle_inputs: args.subtitle_input,
+        remove_targets_inputs: args.remove_targets_input,
+        opcode_tables: args.opcode_table,
+        pad_missing_args: args.pad_missing_args,
+        offset,
+        retime_edits,
+        medley: args.medley,
+        input_order: args.input_order,
+        output: args.output,
         game,
-        args.pv_id,
-        args.english_lyrics,
-        args.max_lyric_length,
-        args.dump,
-        args.verbose,
-        challenge_time.unwrap(),
-        &mut logger,
-    );
+        convert_to,
+        endianness,
+        pv_id: args.pv_id,
+        english_lyrics: args.english_lyrics,
+        max_lyric_length: args.max_lyric_length,
+        dump: args.dump,
+        dump_output: args.dump_output,
+        verbose: args.verbose,
+        challenge_time,
+    };
+
+    let mut application = Application::new(options, &mut logger);
 
     match application.run() {
         Ok(_) => {
-            println!("Done!");
+            eprintln!("Done!");
+            Ok(())
+        }
+        Err(e) => Err(format!("Error: {}", e)),
+    }
+}
+
+fn run_command(command: Commands) -> Result<(), String> {
+    let result = match command {
+        Commands::Merge(args) => return merge(args),
+        Commands::Dump(args) => {
+            let game = args.script.prepare()?;
+
+            let time_format = match &args.time_format {
+                Some(format_str) => match TimeFormat::from_string(format_str) {
+                    Some(time_format) => time_format,
+                    None => return Err(format!("Invalid time format: {}", format_str)),
+                },
+                None => TimeFormat::default(),
+            };
+
+            let format = match &args.format {
+                Some(format_str) => match ScriptFormat::from_string(format_str) {
+                    Some(format) => Some(format),
+                    None => return Err(format!("Invalid format: {}", format_str)),
+                },
+                None => None,
+            };
+
+            let options = DumpOptions {
+                time_format,
+                annotate: args.annotate,
+                group_by_time: args.group,
+            };
+            commands::dump(&args.input, args.output.as_ref(), game, format, &options)
+        }
+        Commands::Compile(args) => {
+            let game = args.script.prepare()?;
+            let endianness = parse_endianness(&args.endianness)?;
+            commands::compile(
+                &args.input,
+                &args.output,
+                game,
+                endianness,
+                args.pad_missing_args,
+            )
+        }
+        Commands::Info(args) => {
+            let game = args.script.prepare()?;
+            args.input
+                .iter()
+                .try_for_each(|input| commands::info(input, game))
+        }
+        Commands::Validate(args) => {
+            let game = args.script.prepare()?;
+            commands::validate_files(&args.input, args.plaintext, args.pad_missing_args, game)
+        }
+        Commands::Diff(args) => {
+            let game = args.script.prepare()?;
+            commands::diff_files(&args.old, &args.new, game)
         }
-        Err(e) => {
-            println!("Error: {}", e);
+    };
+
+    result.map_err(|e| format!("Error: {}", e))
+}
+
+fn main() -> ExitCode {
+    let argc = env::args().len();
+
+    if argc == 1 {
+        // User probably double-clicked the exe
+        #[cfg(feature = "gui")]
+        GUI::new().run();
+
+        #[cfg(not(feature = "gui"))]
+        InteractiveTUI::start();
+
+        return ExitCode::SUCCESS;
+    }
+
+    let matches = Arguments::command().get_matches();
+    let args = Arguments::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let mut command = args.command.unwrap_or(Commands::Merge(args.merge));
+
+    if let Commands::Merge(merge_args) = &mut command {
+        let merge_matches = matches.subcommand_matches("merge").unwrap_or(&matches);
+        merge_args.input_order = input_order(merge_matches);
+
+        if let Err(message) = attach_filters(merge_args, merge_matches) {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match run_command(command) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
         }
     }
 }
@@ -1,16 +1,11 @@
+use std::cmp::Reverse;
 use std::collections::HashMap;
 
-use crate::common::{get_time_command, ChallengeTime, ChallengeTimeDifficulty};
+use crate::common::{get_end_command, get_time_command, ChallengeTime, ChallengeTimeDifficulty};
+use crate::container::PVSCContainer;
 use crate::dsc::DSCVM;
-use crate::opcodes::{Command, Opcode, OpcodeMeta};
-
-const TARGET_COMMAND_OPCODES: [Opcode; 5] = [
-    Opcode::TARGET,
-    Opcode::TARGET_FLYING_TIME,
-    Opcode::TARGET_EFFECT,
-    Opcode::TARGET_FLAG,
-    Opcode::EDIT_TARGET,
-];
+use crate::opcodes::{Command, Opcode, OpcodeCategory, OpcodeMeta};
+use crate::retime::RetimeEdit;
 
 pub struct Event {
     pub time: i32,
@@ -25,16 +20,36 @@ impl Event {
 
 pub struct DSCMerger {
     events: HashMap<i32, Vec<Command>>,
+    container: Option<PVSCContainer>,
+
+    /// Commands that an offset would have moved before the start of the
+    /// script, which were moved to time 0 instead.
+    pub moved_to_start: usize,
+    /// Target commands (see [`OpcodeCategory::Targets`]) that an offset
+    /// would have moved before the start of the script. These are dropped,
+    /// as they couldn't be hit anyway.
+    pub dropped_targets: usize,
+}
+
+impl Default for DSCMerger {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DSCMerger {
     pub fn new() -> Self {
         Self {
             events: HashMap::new(),
+            container: None,
+            moved_to_start: 0,
+            dropped_targets: 0,
         }
     }
 
-    fn add_command(&mut self, timestamp: i32, command: Command) {
+    /// Adds a single command, unless the same command is already at that
+    /// time.
+    pub fn add_command(&mut self, timestamp: i32, command: Command) {
         if let Some(commands) = self.events.get_mut(&timestamp) {
             let mut is_new_command = true;
 
@@ -54,21 +69,67 @@ impl DSCMerger {
     }
 
     pub fn add_dsc(&mut self, dsc_vm: DSCVM) {
-        let mut current_ts = 0;
+        // The time in the input itself, which filters refer to.
+        let mut script_ts = 0;
+        let mut current_ts = dsc_vm.offset;
+
+        // The output keeps the container of the first F2nd/X input.
+        if self.container.is_none() {
+            self.container = dsc_vm.container;
+        }
 
         for command in dsc_vm.command_buffer {
-            if dsc_vm.remove_targets && TARGET_COMMAND_OPCODES.contains(&command.meta.opcode) {
+            if dsc_vm.remove_targets
+                && command.meta.opcode.category() == Some(OpcodeCategory::Targets)
+            {
+                continue;
+            }
+
+            if !dsc_vm
+                .filters
+                .iter()
+                .all(|filter| filter.allows(command.meta.opcode, script_ts))
+            {
                 continue;
             }
 
             if command.meta.opcode == Opcode::TIME {
-                current_ts = command.args[0];
+                script_ts = command.args[0];
+                current_ts = script_ts.saturating_add(dsc_vm.offset);
+            } else if current_ts < 0 {
+                if command.meta.opcode.category() == Some(OpcodeCategory::Targets) {
+                    self.dropped_targets += 1;
+                } else {
+                    self.moved_to_start += 1;
+                    self.add_command(0, command);
+                }
             } else {
                 self.add_command(current_ts, command);
             }
         }
     }
 
+    /// Applies song edits to the merged commands. Every edit point refers to
+    /// the original timeline, so the edits are applied from the last one to
+    /// the first. Returns the number of commands dropped by removed ranges.
+    pub fn retime(&mut self, edits: &[RetimeEdit]) -> usize {
+        let mut edits = edits.to_vec();
+        edits.sort_by_key(|edit| Reverse(edit.position()));
+
+        let mut removed = 0;
+
+        for edit in edits {
+            for (time, commands) in std::mem::take(&mut self.events) {
+                match edit.apply(time) {
+                    Some(time) => self.events.entry(time).or_default().extend(commands),
+                    None => removed += commands.len(),
+                }
+            }
+        }
+
+        removed
+    }
+
     pub fn add_challenge_time(&mut self, challenge_time: ChallengeTime) {
         let start_time = challenge_time.start * 100;
         let end_time = challenge_time.end * 100;
@@ -99,25 +160,169 @@ impl DSCMerger {
             .map(|(time, commands)| Event::new(*time, commands.clone()))
             .collect();
 
-        events.sort_by(|a, b| a.time.cmp(&b.time));
+        events.sort_by_key(|event| event.time);
 
         events
     }
 
-    pub fn to_dsc(&mut self) -> DSCVM {
+    pub fn to_dsc(&self) -> DSCVM {
         let events = self.create_event_vector();
 
         let mut dsc_vm = DSCVM::new(false);
+        dsc_vm.container = self.container.clone();
 
         for event in events {
+            // END terminates the script, so it is left out here and added
+            // once after every other command, whether or not the inputs
+            // (or the sections taken from them) had it.
+            let commands = event
+                .commands
+                .into_iter()
+                .filter(|command| command.meta.opcode != Opcode::END)
+                .collect::<Vec<Command>>();
+
+            if commands.is_empty() {
+                continue;
+            }
+
             let time_command = get_time_command(event.time);
             dsc_vm.add_command(time_command);
 
-            for command in &event.commands {
-                dsc_vm.add_command(command.clone());
+            for command in commands {
+                dsc_vm.add_command(command);
             }
         }
 
+        dsc_vm.add_command(get_end_command());
+
+        dsc_vm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Game;
+
+    fn command(name: &str, args: Vec<i32>) -> Command {
+        let meta = Command::get_opcode_meta_from_name(Game::FutureTone, name.to_string()).unwrap();
+        Command::new(meta, args)
+    }
+
+    fn script(commands: Vec<Command>) -> DSCVM {
+        let mut dsc_vm = DSCVM::new(false);
+
+        for command in commands {
+            dsc_vm.add_command(command);
+        }
+
         dsc_vm
     }
+
+    fn opcodes_at(merger: &DSCMerger, time: i32) -> Vec<&'static str> {
+        merger.events.get(&time).map_or_else(Vec::new, |commands| {
+            commands
+                .iter()
+                .map(|command| command.meta.opcode.name())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn negative_offset_drops_target_commands() {
+        let mut dsc_vm = script(vec![
+            command("TIME", vec![0]),
+            command("MUSIC_PLAY", vec![]),
+            command("TARGET_FLYING_TIME", vec![1000]),
+            command("TARGET", vec![0, 480000, 240000, 0, 0, 0, 0]),
+            command("EDIT_TARGET", vec![0, 0, 0, 0, 0]),
+            command("TIME", vec![200000]),
+            command("TARGET", vec![1, 480000, 240000, 0, 0, 0, 0]),
+        ]);
+        dsc_vm.offset = -100000;
+
+        let mut merger = DSCMerger::new();
+        merger.add_dsc(dsc_vm);
+
+        assert_eq!(merger.dropped_targets, 3);
+        assert_eq!(merger.moved_to_start, 1);
+        assert_eq!(opcodes_at(&merger, 0), vec!["MUSIC_PLAY"]);
+        assert_eq!(opcodes_at(&merger, 100000), vec!["TARGET"]);
+    }
+
+    fn retimed(edits: &[RetimeEdit]) -> (DSCMerger, usize) {
+        let mut merger = DSCMerger::new();
+        merger.add_command(0, command("MUSIC_PLAY", vec![]));
+        merger.add_command(100000, command("TARGET", vec![0, 0, 0, 0, 0, 0, 0]));
+        merger.add_command(250000, command("TARGET", vec![1, 0, 0, 0, 0, 0, 0]));
+        merger.add_command(300000, command("TARGET", vec![2, 0, 0, 0, 0, 0, 0]));
+        merger.add_command(300000, command("TARGET_FLYING_TIME", vec![1000]));
+        merger.add_command(350000, command("TARGET", vec![3, 0, 0, 0, 0, 0, 0]));
+        merger.add_command(500000, command("PV_END", vec![]));
+
+        let removed = merger.retime(edits);
+
+        (merger, removed)
+    }
+
+    fn times(merger: &DSCMerger) -> Vec<i32> {
+        let mut times = merger.events.keys().copied().collect::<Vec<i32>>();
+        times.sort();
+        times
+    }
+
+    #[test]
+    fn retime_insert_before_remove() {
+        let insert = RetimeEdit::Insert {
+            at: 50000,
+            duration: 20000,
+        };
+        let remove = RetimeEdit::Remove {
+            start: 250000,
+            end: 350000,
+        };
+
+        // Both edits refer to the original timeline, whatever their order.
+        for edits in [[insert, remove], [remove, insert]] {
+            let (merger, _) = retimed(&edits);
+
+            assert_eq!(times(&merger), vec![0, 120000, 270000, 420000]);
+            assert_eq!(opcodes_at(&merger, 270000), vec!["TARGET"]);
+            assert_eq!(merger.events[&270000][0].args[0], 3);
+            assert_eq!(opcodes_at(&merger, 420000), vec!["PV_END"]);
+        }
+    }
+
+    #[test]
+    fn retime_remove_drops_overlapped_commands() {
+        let (merger, removed) = retimed(&[RetimeEdit::Remove {
+            start: 250000,
+            end: 350000,
+        }]);
+
+        // The start of the range is cut out, the end is kept.
+        assert_eq!(removed, 3);
+        assert_eq!(times(&merger), vec![0, 100000, 250000, 400000]);
+        assert_eq!(merger.events[&250000][0].args[0], 3);
+    }
+
+    #[test]
+    fn retime_counts_dropped_commands_of_every_remove() {
+        let (_, removed) = retimed(&[
+            RetimeEdit::Remove {
+                start: 0,
+                end: 100001,
+            },
+            RetimeEdit::Remove {
+                start: 300000,
+                end: 300001,
+            },
+            RetimeEdit::Insert {
+                at: 400000,
+                duration: 100000,
+            },
+        ]);
+
+        assert_eq!(removed, 4);
+    }
 }
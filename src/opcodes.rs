@@ -1,197 +1,396 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::Read;
+use std::sync::{LazyLock, Mutex, RwLock};
+
 use crate::{
     common::Game,
     error::{ApplicationError, ApplicationResult},
 };
 
-#[allow(non_camel_case_types)]
+/// Opcode tables for each game. Every non-empty line that doesn't start with
+/// `#` describes one opcode as `<id> <name> <params...>`, where each
+/// parameter is written as `<name>` or `<name>:<kind>` (see [`ParamKind`]).
+/// The number of parameters is the parameter count of the opcode.
+const F_OPCODES: &str = include_str!("opcodes/f.txt");
+const F2ND_OPCODES: &str = include_str!("opcodes/f2nd.txt");
+const X_OPCODES: &str = include_str!("opcodes/x.txt");
+const FT_OPCODES: &str = include_str!("opcodes/ft.txt");
+
+/// The category of each opcode, see [`OpcodeCategory`].
+const OPCODE_CATEGORIES: &str = include_str!("opcodes/categories.txt");
+
+static BUILTIN_TABLES: LazyLock<HashMap<Game, OpcodeTable>> = LazyLock::new(|| {
+    let mut tables = HashMap::new();
+
+    for (game, source) in [
+        (Game::F, F_OPCODES),
+        (Game::F2nd, F2ND_OPCODES),
+        (Game::X, X_OPCODES),
+        (Game::FutureTone, FT_OPCODES),
+    ] {
+        let mut table = OpcodeTable::new();
+
+        for (line_number, line) in source.lines().enumerate() {
+            if is_blank_or_comment(line) {
+                continue;
+            }
+
+            let meta = parse_opcode_definition(line)
+                .unwrap_or_else(|| panic!("invalid built-in opcode on line {}", line_number + 1));
+
+            table.insert(meta);
+        }
+
+        tables.insert(game, table);
+    }
+
+    tables
+});
+
+/// Definitions loaded with [`load_opcode_definitions`], which take
+/// precedence over the built-in ones.
+static CUSTOM_TABLES: LazyLock<RwLock<HashMap<Game, OpcodeTable>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Custom definitions by the line they were parsed from. Opcodes refer to
+/// their names for the rest of the program, so each distinct line is only
+/// leaked once, no matter how often a table is loaded again.
+static CUSTOM_DEFINITIONS: LazyLock<Mutex<HashMap<String, Option<OpcodeMeta>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static CATEGORIES: LazyLock<HashMap<&'static str, OpcodeCategory>> = LazyLock::new(|| {
+    let mut categories = HashMap::new();
+    let mut category = None;
+
+    for (line_number, line) in OPCODE_CATEGORIES.lines().enumerate() {
+        if is_blank_or_comment(line) {
+            continue;
+        }
+
+        if let Some(section) = line
+            .trim()
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            category =
+                Some(OpcodeCategory::from_string(section).unwrap_or_else(|| {
+                    panic!("unknown opcode category on line {}", line_number + 1)
+                }));
+            continue;
+        }
+
+        let category = category
+            .unwrap_or_else(|| panic!("opcodes outside of a category on line {}", line_number + 1));
+
+        for name in line.split_whitespace() {
+            categories.insert(name, category);
+        }
+    }
+
+    categories
+});
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// Parses a single line of an opcode table, returning `None` if the line is
+/// malformed. Instead of naming every parameter, a definition may also just
+/// give the parameter count (`300 MY_OPCODE 3`).
+fn parse_opcode_definition(line: &'static str) -> Option<OpcodeMeta> {
+    let mut columns = line.split_whitespace();
+
+    let id = columns.next()?.parse::<i32>().ok()?;
+    let name = columns.next()?;
+    let columns = columns.collect::<Vec<&str>>();
+
+    if let [count] = columns[..] {
+        if let Ok(param_count) = count.parse::<usize>() {
+            return Some(OpcodeMeta::new(id, Opcode::new(name), param_count));
+        }
+    }
+
+    let mut params = Vec::new();
+
+    for column in columns {
+        let (param_name, kind) = match column.split_once(':') {
+            Some((param_name, kind)) => (param_name, ParamKind::from_string(kind)?),
+            None => (column, ParamKind::Int),
+        };
+
+        params.push(OpcodeParam {
+            name: param_name,
+            kind,
+        });
+    }
+
+    Some(OpcodeMeta {
+        id,
+        opcode: Opcode::new(name),
+        param_count: params.len(),
+        params: params.leak(),
+    })
+}
+
+fn parse_custom_definition(line: &str) -> Option<OpcodeMeta> {
+    let line = line.trim();
+    let mut definitions = CUSTOM_DEFINITIONS.lock().unwrap();
+
+    if let Some(&meta) = definitions.get(line) {
+        return meta;
+    }
+
+    let meta = parse_opcode_definition(line.to_string().leak());
+    definitions.insert(line.to_string(), meta);
+
+    meta
+}
+
+/// Loads extra opcode definitions, e.g. for opcodes added by mods. The file
+/// uses the same format as the built-in tables, split into sections by game:
+///
+/// ```text
+/// [ft]
+/// 300 MY_CUSTOM_OPCODE chara:chara value
+/// 301 MY_OTHER_OPCODE 4
+/// ```
+///
+/// Definitions replace any built-in opcode with the same id, until
+/// [`clear_opcode_definitions`] is called. Future Tone and Arcade share a
+/// table, so definitions for either apply to both. Nothing is loaded if the
+/// file has a mistake in it.
+pub fn load_opcode_definitions<R: Read>(reader: &mut R) -> ApplicationResult {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+
+    let mut loaded: HashMap<Game, OpcodeTable> = HashMap::new();
+    let mut game: Option<Game> = None;
+
+    for (line_number, line) in source.lines().enumerate() {
+        let error =
+            || ApplicationError::InvalidOpcodeDefinition(line_number + 1, line.trim().to_string());
+
+        if is_blank_or_comment(line) {
+            continue;
+        }
+
+        if let Some(section) = line
+            .trim()
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            game = Some(Game::from_string(section.trim()).ok_or_else(error)?);
+            continue;
+        }
+
+        let meta = parse_custom_definition(line).ok_or_else(error)?;
+        let game = OpcodeTable::game_key(game.ok_or_else(error)?);
+
+        loaded
+            .entry(game)
+            .or_insert_with(OpcodeTable::new)
+            .insert(meta);
+    }
+
+    let mut tables = CUSTOM_TABLES.write().unwrap();
+
+    for (game, table) in loaded {
+        let custom = tables.entry(game).or_insert_with(OpcodeTable::new);
+
+        for meta in table.by_id.into_values() {
+            custom.insert(meta);
+        }
+    }
+
+    Ok(())
+}
+
+/// Forgets the definitions loaded with [`load_opcode_definitions`], leaving
+/// only the built-in opcodes.
+pub fn clear_opcode_definitions() {
+    CUSTOM_TABLES.write().unwrap().clear();
+}
+
+/// An opcode, identified by its name. The same opcode usually has a
+/// different id (and sometimes a different parameter count) in each game.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub struct Opcode(&'static str);
+
+impl Opcode {
+    pub const END: Opcode = Opcode("END");
+    pub const TIME: Opcode = Opcode("TIME");
+    pub const TARGET: Opcode = Opcode("TARGET");
+    pub const TARGET_FLYING_TIME: Opcode = Opcode("TARGET_FLYING_TIME");
+    pub const TARGET_EFFECT: Opcode = Opcode("TARGET_EFFECT");
+    pub const TARGET_FLAG: Opcode = Opcode("TARGET_FLAG");
+    pub const EDIT_TARGET: Opcode = Opcode("EDIT_TARGET");
+    pub const LYRIC: Opcode = Opcode("LYRIC");
+    pub const MUSIC_PLAY: Opcode = Opcode("MUSIC_PLAY");
+    pub const BAR_TIME_SET: Opcode = Opcode("BAR_TIME_SET");
+    pub const MODE_SELECT: Opcode = Opcode("MODE_SELECT");
+    pub const PV_END: Opcode = Opcode("PV_END");
+
+    pub const fn new(name: &'static str) -> Self {
+        Self(name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    /// Returns what the opcode is used for, or `None` for opcodes that
+    /// aren't built in.
+    pub fn category(&self) -> Option<OpcodeCategory> {
+        CATEGORIES.get(self.0).copied()
+    }
+
+    /// Whether `name` is exactly the name of an opcode of any game, built in
+    /// or loaded with [`load_opcode_definitions`].
+    pub fn is_known_name(name: &str) -> bool {
+        let custom = CUSTOM_TABLES.read().unwrap();
+
+        BUILTIN_TABLES
+            .values()
+            .chain(custom.values())
+            .any(|table| table.by_name.contains_key(name))
+    }
+}
+
+impl Debug for Opcode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for Opcode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A group of opcodes that are used for the same part of a PV, so that they
+/// can be handled together without naming every opcode.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OpcodeCategory {
+    Targets,
+    Camera,
+    /// Character placement, motions and items.
+    Chara,
+    /// Facial expressions, eyes and mouths.
+    Face,
+    Lyrics,
+    /// Stage fields and effects.
+    Stage,
+    /// Lighting, shadows and post-processing.
+    Lighting,
+    Movie,
+    /// Timing, music and the flow of the script.
+    System,
+    /// Commands of the VR modes of X.
+    VR,
+}
+
+impl OpcodeCategory {
+    pub const ALL: [OpcodeCategory; 10] = [
+        OpcodeCategory::Targets,
+        OpcodeCategory::Camera,
+        OpcodeCategory::Chara,
+        OpcodeCategory::Face,
+        OpcodeCategory::Lyrics,
+        OpcodeCategory::Stage,
+        OpcodeCategory::Lighting,
+        OpcodeCategory::Movie,
+        OpcodeCategory::System,
+        OpcodeCategory::VR,
+    ];
+
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "targets" | "target" => Some(OpcodeCategory::Targets),
+            "camera" => Some(OpcodeCategory::Camera),
+            "chara" | "character" | "motion" => Some(OpcodeCategory::Chara),
+            "face" => Some(OpcodeCategory::Face),
+            "lyrics" | "lyric" => Some(OpcodeCategory::Lyrics),
+            "stage" | "field" => Some(OpcodeCategory::Stage),
+            "lighting" | "light" | "post" => Some(OpcodeCategory::Lighting),
+            "movie" => Some(OpcodeCategory::Movie),
+            "system" => Some(OpcodeCategory::System),
+            "vr" => Some(OpcodeCategory::VR),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OpcodeCategory::Targets => "targets",
+            OpcodeCategory::Camera => "camera",
+            OpcodeCategory::Chara => "chara",
+            OpcodeCategory::Face => "face",
+            OpcodeCategory::Lyrics => "lyrics",
+            OpcodeCategory::Stage => "stage",
+            OpcodeCategory::Lighting => "lighting",
+            OpcodeCategory::Movie => "movie",
+            OpcodeCategory::System => "system",
+            OpcodeCategory::VR => "vr",
+        }
+    }
+
+    /// A short description for menus.
+    pub fn description(&self) -> &'static str {
+        match self {
+            OpcodeCategory::Targets => "Targets",
+            OpcodeCategory::Camera => "Camera",
+            OpcodeCategory::Chara => "Characters and motions",
+            OpcodeCategory::Face => "Faces",
+            OpcodeCategory::Lyrics => "Lyrics",
+            OpcodeCategory::Stage => "Stage and fields",
+            OpcodeCategory::Lighting => "Lighting and post-processing",
+            OpcodeCategory::Movie => "Movies",
+            OpcodeCategory::System => "System",
+            OpcodeCategory::VR => "VR",
+        }
+    }
+}
+
+impl Display for OpcodeCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Roughly what a parameter holds, so that it can be presented in a more
+/// readable form.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParamKind {
+    Int,
+    /// A point in time, in 1/100000ths of a second (like `TIME`).
+    Time,
+    /// A duration in milliseconds.
+    Duration,
+    /// The index of a performer.
+    Chara,
+    Bool,
+}
+
+impl ParamKind {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "int" => Some(ParamKind::Int),
+            "time" => Some(ParamKind::Time),
+            "ms" => Some(ParamKind::Duration),
+            "chara" => Some(ParamKind::Chara),
+            "bool" => Some(ParamKind::Bool),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub enum Opcode {
-    AGEAGE_CTRL,
-    AIM,
-    ANNOTATION,
-    AOTO_CAP,
-    AUTO_BLINK,
-    AUTO_CAPTURE_BEGIN,
-    BANK_BRANCH,
-    BANK_END,
-    BAR_POINT,
-    BAR_TIME_SET,
-    BEAT_POINT,
-    BLOOM,
-    CHANGE_FIELD,
-    CHARA_ALPHA,
-    CHARA_COLOR,
-    CHARA_EFFECT,
-    CHARA_EFFECT_CHARA_LIGHT,
-    CHARA_HEIGHT_ADJUST,
-    CHARA_LIGHT,
-    CHARA_POS_ADJUST,
-    CHARA_SHADOW_QUALITY,
-    CHARA_SIZE,
-    CHROMATIC_ABERRATION,
-    CLOTH_WET,
-    COLOR_COLLE,
-    COLOR_CORRECTION,
-    COMMON_EFFECT_AET_FRONT,
-    COMMON_EFFECT_AET_FRONT_LOW,
-    COMMON_EFFECT_PARTICLE,
-    COMMON_LIGHT,
-    CREDIT_TITLE,
-    CROSSFADE,
-    DATA_CAMERA,
-    DATA_CAMERA_START,
-    DOF,
-    DUMMY,
-    EDIT_BLUSH,
-    EDIT_CAMERA,
-    EDIT_CAMERA_BOX,
-    EDIT_CHANGE_FIELD,
-    EDIT_DISP,
-    EDIT_EFFECT,
-    EDIT_EXPRESSION,
-    EDIT_EYE,
-    EDIT_EYELID,
-    EDIT_EYELID_ANIM,
-    EDIT_EYE_ANIM,
-    EDIT_FACE,
-    EDIT_HAND_ANIM,
-    EDIT_INSTRUMENT_ITEM,
-    EDIT_ITEM,
-    EDIT_LYRIC,
-    EDIT_MODE_SELECT,
-    EDIT_MOTION,
-    EDIT_MOTION_F,
-    EDIT_MOTION_LOOP,
-    EDIT_MOT_SMOOTH_LEN,
-    EDIT_MOUTH,
-    EDIT_MOUTH_ANIM,
-    EDIT_MOVE,
-    EDIT_MOVE_XYZ,
-    EDIT_SHADOW,
-    EDIT_STAGE_PARAM,
-    EDIT_TARGET,
-    EFFECT,
-    EFFECT_OFF,
-    ENABLE_COMMON_LIGHT_TO_CHARA,
-    ENABLE_FXAA,
-    ENABLE_REFLECTION,
-    ENABLE_TEMPORAL_AA,
-    END,
-    EVENT_JUDGE,
-    EXPRESSION,
-    EYE_ANIM,
-    FACE_TYPE,
-    FADE,
-    FADEIN_FIELD,
-    FADEOUT_FIELD,
-    FADE_MODE,
-    FOG,
-    FOG_ENABLE,
-    GAZE,
-    HAND_ANIM,
-    HAND_ITEM,
-    HAND_SCALE,
-    HIDE_FIELD,
-    IBL_COLOR,
-    ITEM_ALPHA,
-    ITEM_ANIM,
-    ITEM_ANIM_ATTACH,
-    ITEM_LIGHT,
-    LIGHT_AUTH,
-    LIGHT_POS,
-    LIGHT_ROT,
-    LOOK_ANIM,
-    LOOK_CAMERA,
-    LOOK_CAMERA_FACE_LIMIT,
-    LYRIC,
-    LYRIC_2,
-    LYRIC_READ,
-    LYRIC_READ_2,
-    MANUAL_CAPTURE,
-    MAN_CAP,
-    MARKER,
-    MIKUDAYO_ADJUST,
-    MIKU_DISP,
-    MIKU_MOVE,
-    MIKU_ROT,
-    MIKU_SHADOW,
-    MODE_SELECT,
-    MOUTH_ANIM,
-    MOVE_CAMERA,
-    MOVE_FIELD,
-    MOVIE_CUT,
-    MOVIE_CUT_CHG,
-    MOVIE_DISP,
-    MOVIE_PLAY,
-    MUSIC_PLAY,
-    NEAR_CLIP,
-    OSAGE_MV_CCL,
-    OSAGE_STEP,
-    PARTS_DISP,
-    PSE,
-    PV_AUTH_LIGHT_PRIORITY,
-    PV_BRANCH_MODE,
-    PV_CHARA_LIGHT,
-    PV_END,
-    PV_END_FADEOUT,
-    PV_STAGE_LIGHT,
-    REFLECTION,
-    REFLECTION_QUALITY,
-    RESERVE,
-    SATURATE,
-    SCENE_FADE,
-    SCENE_ROT,
-    SET_CAMERA,
-    SET_CHARA,
-    SET_MOTION,
-    SET_PLAYDATA,
-    SET_STAGE_EFFECT_ENV,
-    SE_EFFECT,
-    SHADOWHEIGHT,
-    SHADOWPOS,
-    SHADOW_CAST,
-    SHADOW_RANGE,
-    SHIMMER,
-    SONG_EFFECT,
-    SONG_EFFECT_ALPHA_SORT,
-    SONG_EFFECT_ATTACH,
-    STAGE_EFFECT,
-    STAGE_LIGHT,
-    STAGE_SHADOW,
-    STAGE_SHADOW_QUALITY,
-    SUBFRAMERENDER,
-    TARGET,
-    TARGET_EFFECT,
-    TARGET_FLAG,
-    TARGET_FLYING_TIME,
-    TECH_DEMO_GESUTRE,
-    TIME,
-    TONE_MAP,
-    TONE_TRANS,
-    TOON,
-    TOON_EDGE,
-    TOON＿EDGE,
-    VR_CHARA_PSMOVE,
-    VR_CHEER,
-    VR_CHEMICAL_LIGHT_COLOR,
-    VR_LIVE_CHARA_VOICE,
-    VR_LIVE_CHEER,
-    VR_LIVE_CLONE,
-    VR_LIVE_FLY,
-    VR_LIVE_GESTURE,
-    VR_LIVE_HAIR_OSAGE,
-    VR_LIVE_LOOK_CAMERA,
-    VR_LIVE_MOB,
-    VR_LIVE_MOVIE,
-    VR_LIVE_ONESHOT_EFFECT,
-    VR_LIVE_PRESENT,
-    VR_LIVE_TRANSFORM,
-    VR_LOOP_EFFECT,
-    VR_MOVE_PATH,
-    VR_SET_BASE,
-    VR_TECH_DEMO_EFFECT,
-    VR_TRANSFORM,
-    WIND,
+pub struct OpcodeParam {
+    pub name: &'static str,
+    pub kind: ParamKind,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -199,6 +398,7 @@ pub struct OpcodeMeta {
     pub id: i32,
     pub opcode: Opcode,
     pub param_count: usize,
+    pub params: &'static [OpcodeParam],
 }
 
 impl OpcodeMeta {
@@ -207,6 +407,83 @@ impl OpcodeMeta {
             id,
             opcode,
             param_count,
+            params: &[],
+        }
+    }
+
+    /// Returns the name of the parameter at `index`, falling back to its
+    /// position (`p1`, `p2`, ...) if the opcode table doesn't name it.
+    pub fn param_name(&self, index: usize) -> String {
+        match self.params.get(index) {
+            Some(param) => param.name.to_string(),
+            None => format!("p{}", index + 1),
+        }
+    }
+}
+
+/// The opcodes of a single game, indexed by id and by name.
+struct OpcodeTable {
+    by_id: HashMap<i32, OpcodeMeta>,
+    by_name: HashMap<&'static str, i32>,
+}
+
+impl OpcodeTable {
+    fn new() -> Self {
+        Self {
+            by_id: HashMap::new(),
+            by_name: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, meta: OpcodeMeta) {
+        if let Some(replaced) = self.by_id.insert(meta.id, meta) {
+            let name = replaced.opcode.name();
+
+            if self.by_name.get(name) == Some(&meta.id) {
+                self.by_name.remove(name);
+            }
+        }
+
+        self.by_name.insert(meta.opcode.name(), meta.id);
+    }
+
+    fn lookup(&self, name: &str) -> Option<OpcodeMeta> {
+        self.by_name
+            .get(name)
+            .and_then(|id| self.by_id.get(id))
+            .copied()
+    }
+
+    fn get_meta(game: Game, id: i32) -> Option<OpcodeMeta> {
+        let game = Self::game_key(game);
+        let custom = CUSTOM_TABLES.read().unwrap();
+
+        custom
+            .get(&game)
+            .and_then(|table| table.by_id.get(&id))
+            .or_else(|| BUILTIN_TABLES[&game].by_id.get(&id))
+            .copied()
+    }
+
+    fn get_meta_from_name(game: Game, name: &str) -> Option<OpcodeMeta> {
+        let game = Self::game_key(game);
+        let custom = CUSTOM_TABLES.read().unwrap();
+        let custom = custom.get(&game);
+
+        if let Some(meta) = custom.and_then(|table| table.lookup(name)) {
+            return Some(meta);
+        }
+
+        // A custom definition with the same id replaces the built-in opcode.
+        BUILTIN_TABLES[&game]
+            .lookup(name)
+            .filter(|meta| !custom.is_some_and(|table| table.by_id.contains_key(&meta.id)))
+    }
+
+    fn game_key(game: Game) -> Game {
+        match game {
+            Game::Arcade => Game::FutureTone,
+            game => game,
         }
     }
 }
@@ -222,8 +499,59 @@ impl Command {
         Self { meta, args }
     }
 
-    pub fn to_string(&self) -> String {
-        let command_name = format!("{:?}", self.meta.opcode);
+    pub fn get_opcode_meta(game: Game, raw: i32) -> ApplicationResult<OpcodeMeta> {
+        match OpcodeTable::get_meta(game, raw) {
+            Some(meta) => Ok(meta),
+            None => Err(ApplicationError::UnknownOpcode(raw)),
+        }
+    }
+
+    pub fn get_opcode_meta_from_name(game: Game, name: String) -> ApplicationResult<OpcodeMeta> {
+        match OpcodeTable::get_meta_from_name(game, &name) {
+            Some(meta) => Ok(meta),
+            None => Err(ApplicationError::UnknownOpcodeName(name)),
+        }
+    }
+
+    /// Looks up the id and parameter count an opcode has in the given game,
+    /// if the game supports it at all.
+    pub fn get_opcode_meta_from_opcode(game: Game, opcode: Opcode) -> Option<OpcodeMeta> {
+        Command::get_opcode_meta_from_name(game, opcode.name().to_string()).ok()
+    }
+}
+
+/// The values `TARGET` parameters have when a script doesn't give them:
+/// holds are off, and the flying time and time signature are the ones last
+/// set by `TARGET_FLYING_TIME` and `BAR_TIME_SET`. Every command of the
+/// script has to be passed to [`TargetDefaults::track`] in order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TargetDefaults {
+    flying_time: Option<i32>,
+    time_signature: Option<i32>,
+}
+
+impl TargetDefaults {
+    pub fn track(&mut self, command: &Command) {
+        match command.meta.opcode {
+            Opcode::TARGET_FLYING_TIME => self.flying_time = command.args.first().copied(),
+            Opcode::BAR_TIME_SET => self.time_signature = command.args.get(1).copied(),
+            _ => {}
+        }
+    }
+
+    /// The default of the `TARGET` parameter called `name`, if it has one.
+    pub fn get(&self, name: &str) -> Option<i32> {
+        match name {
+            "hold_length" | "hold_end" => Some(-1),
+            "flying_time" => self.flying_time,
+            "time_signature" => self.time_signature,
+            _ => None,
+        }
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let args = self
             .args
             .iter()
@@ -231,512 +559,55 @@ impl Command {
             .collect::<Vec<String>>()
             .join(", ");
 
-        format!("{}({});", command_name, args)
+        write!(f, "{}({});", self.meta.opcode, args)
     }
+}
 
-    pub fn get_opcode_meta(game: Game, raw: i32) -> ApplicationResult<OpcodeMeta> {
-        match game {
-            Game::F => match raw {
-                0 => Ok(OpcodeMeta::new(raw, Opcode::END, 0)),
-                1 => Ok(OpcodeMeta::new(raw, Opcode::TIME, 1)),
-                2 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_MOVE, 4)),
-                3 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_ROT, 2)),
-                4 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_DISP, 2)),
-                5 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_SHADOW, 2)),
-                6 => Ok(OpcodeMeta::new(raw, Opcode::TARGET, 11)),
-                7 => Ok(OpcodeMeta::new(raw, Opcode::SET_MOTION, 4)),
-                8 => Ok(OpcodeMeta::new(raw, Opcode::SET_PLAYDATA, 2)),
-                9 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT, 6)),
-                10 => Ok(OpcodeMeta::new(raw, Opcode::FADEIN_FIELD, 2)),
-                11 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT_OFF, 1)),
-                12 => Ok(OpcodeMeta::new(raw, Opcode::SET_CAMERA, 6)),
-                13 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA, 2)),
-                14 => Ok(OpcodeMeta::new(raw, Opcode::CHANGE_FIELD, 1)),
-                15 => Ok(OpcodeMeta::new(raw, Opcode::HIDE_FIELD, 1)),
-                16 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_FIELD, 3)),
-                17 => Ok(OpcodeMeta::new(raw, Opcode::FADEOUT_FIELD, 2)),
-                18 => Ok(OpcodeMeta::new(raw, Opcode::EYE_ANIM, 3)),
-                19 => Ok(OpcodeMeta::new(raw, Opcode::MOUTH_ANIM, 5)),
-                20 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ANIM, 5)),
-                21 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_ANIM, 4)),
-                22 => Ok(OpcodeMeta::new(raw, Opcode::EXPRESSION, 4)),
-                23 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_CAMERA, 5)),
-                24 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC, 2)),
-                25 => Ok(OpcodeMeta::new(raw, Opcode::MUSIC_PLAY, 0)),
-                26 => Ok(OpcodeMeta::new(raw, Opcode::MODE_SELECT, 2)),
-                27 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION, 4)),
-                28 => Ok(OpcodeMeta::new(raw, Opcode::BAR_TIME_SET, 2)),
-                29 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWHEIGHT, 2)),
-                30 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_FACE, 1)),
-                31 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_CAMERA, 21)),
-                32 => Ok(OpcodeMeta::new(raw, Opcode::PV_END, 0)),
-                33 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWPOS, 3)),
-                34 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_LYRIC, 2)),
-                35 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_TARGET, 5)),
-                36 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH, 1)),
-                37 => Ok(OpcodeMeta::new(raw, Opcode::SET_CHARA, 1)),
-                38 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE, 7)),
-                39 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_SHADOW, 1)),
-                40 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID, 1)),
-                41 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE, 2)),
-                42 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_ITEM, 1)),
-                43 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EFFECT, 2)),
-                44 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_DISP, 1)),
-                45 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_HAND_ANIM, 2)),
-                46 => Ok(OpcodeMeta::new(raw, Opcode::AIM, 3)),
-                47 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ITEM, 3)),
-                48 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_BLUSH, 1)),
-                49 => Ok(OpcodeMeta::new(raw, Opcode::NEAR_CLIP, 2)),
-                50 => Ok(OpcodeMeta::new(raw, Opcode::CLOTH_WET, 2)),
-                51 => Ok(OpcodeMeta::new(raw, Opcode::LIGHT_ROT, 3)),
-                52 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_FADE, 6)),
-                53 => Ok(OpcodeMeta::new(raw, Opcode::TONE_TRANS, 6)),
-                54 => Ok(OpcodeMeta::new(raw, Opcode::SATURATE, 1)),
-                55 => Ok(OpcodeMeta::new(raw, Opcode::FADE_MODE, 1)),
-                56 => Ok(OpcodeMeta::new(raw, Opcode::AUTO_BLINK, 2)),
-                57 => Ok(OpcodeMeta::new(raw, Opcode::PARTS_DISP, 3)),
-                58 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_FLYING_TIME, 1)),
-                59 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_SIZE, 2)),
-                60 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_HEIGHT_ADJUST, 2)),
-                61 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ANIM, 4)),
-                62 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_POS_ADJUST, 4)),
-                63 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_ROT, 1)),
-                64 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOT_SMOOTH_LEN, 2)),
-                65 => Ok(OpcodeMeta::new(raw, Opcode::PV_BRANCH_MODE, 1)),
-                66 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA_START, 2)),
-                67 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_PLAY, 1)),
-                68 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_DISP, 1)),
-                69 => Ok(OpcodeMeta::new(raw, Opcode::WIND, 3)),
-                70 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_STEP, 3)),
-                71 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_MV_CCL, 3)),
-                72 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_COLOR, 2)),
-                73 => Ok(OpcodeMeta::new(raw, Opcode::SE_EFFECT, 1)),
-                74 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE_XYZ, 9)),
-                75 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID_ANIM, 3)),
-                76 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_INSTRUMENT_ITEM, 2)),
-                77 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION_LOOP, 4)),
-                78 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EXPRESSION, 2)),
-                79 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE_ANIM, 3)),
-                80 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH_ANIM, 2)),
-                81 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CAMERA, 24)),
-                82 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MODE_SELECT, 1)),
-                83 => Ok(OpcodeMeta::new(raw, Opcode::PV_END_FADEOUT, 2)),
-                _ => Err(ApplicationError::UnknownOpcode(raw)),
-            },
-            Game::F2nd => match raw {
-                0 => Ok(OpcodeMeta::new(raw, Opcode::END, 0)),
-                1 => Ok(OpcodeMeta::new(raw, Opcode::TIME, 1)),
-                2 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_MOVE, 4)),
-                3 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_ROT, 2)),
-                4 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_DISP, 2)),
-                5 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_SHADOW, 2)),
-                6 => Ok(OpcodeMeta::new(raw, Opcode::TARGET, 12)),
-                7 => Ok(OpcodeMeta::new(raw, Opcode::SET_MOTION, 4)),
-                8 => Ok(OpcodeMeta::new(raw, Opcode::SET_PLAYDATA, 2)),
-                9 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT, 6)),
-                10 => Ok(OpcodeMeta::new(raw, Opcode::FADEIN_FIELD, 2)),
-                11 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT_OFF, 1)),
-                12 => Ok(OpcodeMeta::new(raw, Opcode::SET_CAMERA, 6)),
-                13 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA, 2)),
-                14 => Ok(OpcodeMeta::new(raw, Opcode::CHANGE_FIELD, 2)),
-                15 => Ok(OpcodeMeta::new(raw, Opcode::HIDE_FIELD, 1)),
-                16 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_FIELD, 3)),
-                17 => Ok(OpcodeMeta::new(raw, Opcode::FADEOUT_FIELD, 2)),
-                18 => Ok(OpcodeMeta::new(raw, Opcode::EYE_ANIM, 3)),
-                19 => Ok(OpcodeMeta::new(raw, Opcode::MOUTH_ANIM, 5)),
-                20 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ANIM, 5)),
-                21 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_ANIM, 4)),
-                22 => Ok(OpcodeMeta::new(raw, Opcode::EXPRESSION, 4)),
-                23 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_CAMERA, 5)),
-                24 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC, 2)),
-                25 => Ok(OpcodeMeta::new(raw, Opcode::MUSIC_PLAY, 0)),
-                26 => Ok(OpcodeMeta::new(raw, Opcode::MODE_SELECT, 2)),
-                27 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION, 4)),
-                28 => Ok(OpcodeMeta::new(raw, Opcode::BAR_TIME_SET, 2)),
-                29 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWHEIGHT, 2)),
-                30 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_FACE, 1)),
-                31 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_CAMERA, 21)),
-                32 => Ok(OpcodeMeta::new(raw, Opcode::PV_END, 0)),
-                33 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWPOS, 3)),
-                34 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_LYRIC, 2)),
-                35 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_TARGET, 5)),
-                36 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH, 1)),
-                37 => Ok(OpcodeMeta::new(raw, Opcode::SET_CHARA, 1)),
-                38 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE, 7)),
-                39 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_SHADOW, 1)),
-                40 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID, 1)),
-                41 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE, 2)),
-                42 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_ITEM, 1)),
-                43 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EFFECT, 2)),
-                44 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_DISP, 1)),
-                45 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_HAND_ANIM, 2)),
-                46 => Ok(OpcodeMeta::new(raw, Opcode::AIM, 3)),
-                47 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ITEM, 3)),
-                48 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_BLUSH, 1)),
-                49 => Ok(OpcodeMeta::new(raw, Opcode::NEAR_CLIP, 2)),
-                50 => Ok(OpcodeMeta::new(raw, Opcode::CLOTH_WET, 2)),
-                51 => Ok(OpcodeMeta::new(raw, Opcode::LIGHT_ROT, 3)),
-                52 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_FADE, 6)),
-                53 => Ok(OpcodeMeta::new(raw, Opcode::TONE_TRANS, 6)),
-                54 => Ok(OpcodeMeta::new(raw, Opcode::SATURATE, 1)),
-                55 => Ok(OpcodeMeta::new(raw, Opcode::FADE_MODE, 1)),
-                56 => Ok(OpcodeMeta::new(raw, Opcode::AUTO_BLINK, 2)),
-                57 => Ok(OpcodeMeta::new(raw, Opcode::PARTS_DISP, 3)),
-                58 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_FLYING_TIME, 1)),
-                59 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_SIZE, 2)),
-                60 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_HEIGHT_ADJUST, 2)),
-                61 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ANIM, 4)),
-                62 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_POS_ADJUST, 4)),
-                63 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_ROT, 1)),
-                64 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOT_SMOOTH_LEN, 2)),
-                65 => Ok(OpcodeMeta::new(raw, Opcode::PV_BRANCH_MODE, 1)),
-                66 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA_START, 2)),
-                67 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_PLAY, 1)),
-                68 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_DISP, 1)),
-                69 => Ok(OpcodeMeta::new(raw, Opcode::WIND, 3)),
-                70 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_STEP, 3)),
-                71 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_MV_CCL, 3)),
-                72 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_COLOR, 2)),
-                73 => Ok(OpcodeMeta::new(raw, Opcode::SE_EFFECT, 1)),
-                74 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE_XYZ, 9)),
-                75 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID_ANIM, 3)),
-                76 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_INSTRUMENT_ITEM, 2)),
-                77 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION_LOOP, 4)),
-                78 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EXPRESSION, 2)),
-                79 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE_ANIM, 3)),
-                80 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH_ANIM, 2)),
-                81 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CAMERA, 22)),
-                82 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MODE_SELECT, 1)),
-                83 => Ok(OpcodeMeta::new(raw, Opcode::PV_END_FADEOUT, 2)),
-                87 => Ok(OpcodeMeta::new(raw, Opcode::RESERVE, 9)),
-                88 => Ok(OpcodeMeta::new(raw, Opcode::PV_AUTH_LIGHT_PRIORITY, 2)),
-                89 => Ok(OpcodeMeta::new(raw, Opcode::PV_CHARA_LIGHT, 3)),
-                90 => Ok(OpcodeMeta::new(raw, Opcode::PV_STAGE_LIGHT, 3)),
-                91 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_EFFECT, 11)),
-                92 => Ok(OpcodeMeta::new(raw, Opcode::FOG, 3)),
-                93 => Ok(OpcodeMeta::new(raw, Opcode::BLOOM, 2)),
-                94 => Ok(OpcodeMeta::new(raw, Opcode::COLOR_CORRECTION, 3)),
-                95 => Ok(OpcodeMeta::new(raw, Opcode::DOF, 3)),
-                96 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_ALPHA, 4)),
-                97 => Ok(OpcodeMeta::new(raw, Opcode::AUTO_CAPTURE_BEGIN, 1)),
-                98 => Ok(OpcodeMeta::new(raw, Opcode::MANUAL_CAPTURE, 1)),
-                99 => Ok(OpcodeMeta::new(raw, Opcode::TOON_EDGE, 3)),
-                100 => Ok(OpcodeMeta::new(raw, Opcode::SHIMMER, 3)),
-                101 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ALPHA, 4)),
-                102 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_CUT, 1)),
-                103 => Ok(OpcodeMeta::new(raw, Opcode::CROSSFADE, 1)),
-                104 => Ok(OpcodeMeta::new(raw, Opcode::SUBFRAMERENDER, 1)),
-                105 => Ok(OpcodeMeta::new(raw, Opcode::EVENT_JUDGE, 36)),
-                106 => Ok(OpcodeMeta::new(raw, Opcode::TOON＿EDGE, 2)),
-                107 => Ok(OpcodeMeta::new(raw, Opcode::FOG_ENABLE, 2)),
-                108 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CAMERA_BOX, 112)),
-                109 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_STAGE_PARAM, 1)),
-                110 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CHANGE_FIELD, 1)),
-                _ => Err(ApplicationError::UnknownOpcode(raw)),
-            },
-            Game::X => match raw {
-                0 => Ok(OpcodeMeta::new(raw, Opcode::END, 0)),
-                1 => Ok(OpcodeMeta::new(raw, Opcode::TIME, 1)),
-                2 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_MOVE, 4)),
-                3 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_ROT, 2)),
-                4 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_DISP, 2)),
-                5 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_SHADOW, 2)),
-                6 => Ok(OpcodeMeta::new(raw, Opcode::TARGET, 12)),
-                7 => Ok(OpcodeMeta::new(raw, Opcode::SET_MOTION, 4)),
-                8 => Ok(OpcodeMeta::new(raw, Opcode::SET_PLAYDATA, 2)),
-                9 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT, 6)),
-                10 => Ok(OpcodeMeta::new(raw, Opcode::FADEIN_FIELD, 2)),
-                11 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT_OFF, 1)),
-                12 => Ok(OpcodeMeta::new(raw, Opcode::SET_CAMERA, 6)),
-                13 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA, 2)),
-                14 => Ok(OpcodeMeta::new(raw, Opcode::CHANGE_FIELD, 2)),
-                15 => Ok(OpcodeMeta::new(raw, Opcode::HIDE_FIELD, 1)),
-                16 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_FIELD, 3)),
-                17 => Ok(OpcodeMeta::new(raw, Opcode::FADEOUT_FIELD, 2)),
-                18 => Ok(OpcodeMeta::new(raw, Opcode::EYE_ANIM, 3)),
-                19 => Ok(OpcodeMeta::new(raw, Opcode::MOUTH_ANIM, 5)),
-                20 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ANIM, 5)),
-                21 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_ANIM, 4)),
-                22 => Ok(OpcodeMeta::new(raw, Opcode::EXPRESSION, 4)),
-                23 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_CAMERA, 5)),
-                24 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC, 2)),
-                25 => Ok(OpcodeMeta::new(raw, Opcode::MUSIC_PLAY, 0)),
-                26 => Ok(OpcodeMeta::new(raw, Opcode::MODE_SELECT, 2)),
-                27 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION, 4)),
-                28 => Ok(OpcodeMeta::new(raw, Opcode::BAR_TIME_SET, 2)),
-                29 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWHEIGHT, 2)),
-                30 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_FACE, 1)),
-                31 => Ok(OpcodeMeta::new(raw, Opcode::DUMMY, 21)),
-                32 => Ok(OpcodeMeta::new(raw, Opcode::PV_END, 0)),
-                33 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWPOS, 3)),
-                34 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_LYRIC, 2)),
-                35 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_TARGET, 5)),
-                36 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH, 1)),
-                37 => Ok(OpcodeMeta::new(raw, Opcode::SET_CHARA, 1)),
-                38 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE, 7)),
-                39 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_SHADOW, 1)),
-                40 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID, 1)),
-                41 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE, 2)),
-                42 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_ITEM, 1)),
-                43 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EFFECT, 2)),
-                44 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_DISP, 1)),
-                45 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_HAND_ANIM, 2)),
-                46 => Ok(OpcodeMeta::new(raw, Opcode::AIM, 3)),
-                47 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ITEM, 3)),
-                48 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_BLUSH, 1)),
-                49 => Ok(OpcodeMeta::new(raw, Opcode::NEAR_CLIP, 2)),
-                50 => Ok(OpcodeMeta::new(raw, Opcode::CLOTH_WET, 2)),
-                51 => Ok(OpcodeMeta::new(raw, Opcode::LIGHT_ROT, 3)),
-                52 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_FADE, 6)),
-                53 => Ok(OpcodeMeta::new(raw, Opcode::TONE_TRANS, 6)),
-                54 => Ok(OpcodeMeta::new(raw, Opcode::SATURATE, 1)),
-                55 => Ok(OpcodeMeta::new(raw, Opcode::FADE_MODE, 1)),
-                56 => Ok(OpcodeMeta::new(raw, Opcode::AUTO_BLINK, 2)),
-                57 => Ok(OpcodeMeta::new(raw, Opcode::PARTS_DISP, 3)),
-                58 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_FLYING_TIME, 1)),
-                59 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_SIZE, 2)),
-                60 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_HEIGHT_ADJUST, 2)),
-                61 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ANIM, 4)),
-                62 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_POS_ADJUST, 4)),
-                63 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_ROT, 1)),
-                64 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOT_SMOOTH_LEN, 2)),
-                65 => Ok(OpcodeMeta::new(raw, Opcode::PV_BRANCH_MODE, 1)),
-                66 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA_START, 2)),
-                67 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_PLAY, 1)),
-                68 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_DISP, 1)),
-                69 => Ok(OpcodeMeta::new(raw, Opcode::WIND, 3)),
-                70 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_STEP, 3)),
-                71 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_MV_CCL, 3)),
-                72 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_COLOR, 2)),
-                73 => Ok(OpcodeMeta::new(raw, Opcode::SE_EFFECT, 1)),
-                74 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_SHADOW_QUALITY, 2)),
-                75 => Ok(OpcodeMeta::new(raw, Opcode::STAGE_SHADOW_QUALITY, 2)),
-                76 => Ok(OpcodeMeta::new(raw, Opcode::COMMON_LIGHT, 2)),
-                77 => Ok(OpcodeMeta::new(raw, Opcode::TONE_MAP, 2)),
-                78 => Ok(OpcodeMeta::new(raw, Opcode::IBL_COLOR, 2)),
-                79 => Ok(OpcodeMeta::new(raw, Opcode::REFLECTION, 2)),
-                80 => Ok(OpcodeMeta::new(raw, Opcode::CHROMATIC_ABERRATION, 3)),
-                81 => Ok(OpcodeMeta::new(raw, Opcode::STAGE_SHADOW, 2)),
-                82 => Ok(OpcodeMeta::new(raw, Opcode::REFLECTION_QUALITY, 2)),
-                83 => Ok(OpcodeMeta::new(raw, Opcode::PV_END_FADEOUT, 2)),
-                84 => Ok(OpcodeMeta::new(raw, Opcode::CREDIT_TITLE, 1)),
-                85 => Ok(OpcodeMeta::new(raw, Opcode::BAR_POINT, 1)),
-                86 => Ok(OpcodeMeta::new(raw, Opcode::BEAT_POINT, 1)),
-                88 => Ok(OpcodeMeta::new(raw, Opcode::PV_AUTH_LIGHT_PRIORITY, 2)),
-                89 => Ok(OpcodeMeta::new(raw, Opcode::PV_CHARA_LIGHT, 3)),
-                90 => Ok(OpcodeMeta::new(raw, Opcode::PV_STAGE_LIGHT, 3)),
-                91 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_EFFECT, 11)),
-                92 => Ok(OpcodeMeta::new(raw, Opcode::FOG, 3)),
-                93 => Ok(OpcodeMeta::new(raw, Opcode::BLOOM, 2)),
-                94 => Ok(OpcodeMeta::new(raw, Opcode::COLOR_CORRECTION, 3)),
-                95 => Ok(OpcodeMeta::new(raw, Opcode::DOF, 3)),
-                96 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_ALPHA, 4)),
-                97 => Ok(OpcodeMeta::new(raw, Opcode::AUTO_CAPTURE_BEGIN, 1)),
-                98 => Ok(OpcodeMeta::new(raw, Opcode::MANUAL_CAPTURE, 1)),
-                99 => Ok(OpcodeMeta::new(raw, Opcode::TOON_EDGE, 3)),
-                100 => Ok(OpcodeMeta::new(raw, Opcode::SHIMMER, 3)),
-                101 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ALPHA, 4)),
-                102 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_CUT, 1)),
-                103 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CAMERA_BOX, 112)),
-                104 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_STAGE_PARAM, 1)),
-                105 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CHANGE_FIELD, 1)),
-                106 => Ok(OpcodeMeta::new(raw, Opcode::MIKUDAYO_ADJUST, 7)),
-                107 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC_2, 2)),
-                108 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC_READ, 2)),
-                109 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC_READ_2, 2)),
-                110 => Ok(OpcodeMeta::new(raw, Opcode::ANNOTATION, 5)),
-                111 => Ok(OpcodeMeta::new(raw, Opcode::STAGE_EFFECT, 2)),
-                112 => Ok(OpcodeMeta::new(raw, Opcode::SONG_EFFECT, 3)),
-                113 => Ok(OpcodeMeta::new(raw, Opcode::SONG_EFFECT_ATTACH, 3)),
-                114 => Ok(OpcodeMeta::new(raw, Opcode::LIGHT_AUTH, 2)),
-                115 => Ok(OpcodeMeta::new(raw, Opcode::FADE, 2)),
-                116 => Ok(OpcodeMeta::new(raw, Opcode::SET_STAGE_EFFECT_ENV, 2)),
-                117 => Ok(OpcodeMeta::new(raw, Opcode::RESERVE, 2)),
-                118 => Ok(OpcodeMeta::new(raw, Opcode::COMMON_EFFECT_AET_FRONT, 2)),
-                119 => Ok(OpcodeMeta::new(raw, Opcode::COMMON_EFFECT_AET_FRONT_LOW, 2)),
-                120 => Ok(OpcodeMeta::new(raw, Opcode::COMMON_EFFECT_PARTICLE, 2)),
-                121 => Ok(OpcodeMeta::new(raw, Opcode::SONG_EFFECT_ALPHA_SORT, 3)),
-                122 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_CAMERA_FACE_LIMIT, 5)),
-                123 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_LIGHT, 3)),
-                124 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_EFFECT, 3)),
-                125 => Ok(OpcodeMeta::new(raw, Opcode::MARKER, 2)),
-                126 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_EFFECT_CHARA_LIGHT, 3)),
-                127 => Ok(OpcodeMeta::new(
-                    raw,
-                    Opcode::ENABLE_COMMON_LIGHT_TO_CHARA,
-                    2,
-                )),
-                128 => Ok(OpcodeMeta::new(raw, Opcode::ENABLE_FXAA, 2)),
-                129 => Ok(OpcodeMeta::new(raw, Opcode::ENABLE_TEMPORAL_AA, 2)),
-                130 => Ok(OpcodeMeta::new(raw, Opcode::ENABLE_REFLECTION, 2)),
-                131 => Ok(OpcodeMeta::new(raw, Opcode::BANK_BRANCH, 2)),
-                132 => Ok(OpcodeMeta::new(raw, Opcode::BANK_END, 2)),
-                141 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_MOVIE, 2)),
-                142 => Ok(OpcodeMeta::new(raw, Opcode::VR_CHEER, 2)),
-                143 => Ok(OpcodeMeta::new(raw, Opcode::VR_CHARA_PSMOVE, 2)),
-                144 => Ok(OpcodeMeta::new(raw, Opcode::VR_MOVE_PATH, 2)),
-                145 => Ok(OpcodeMeta::new(raw, Opcode::VR_SET_BASE, 2)),
-                146 => Ok(OpcodeMeta::new(raw, Opcode::VR_TECH_DEMO_EFFECT, 2)),
-                147 => Ok(OpcodeMeta::new(raw, Opcode::VR_TRANSFORM, 2)),
-                148 => Ok(OpcodeMeta::new(raw, Opcode::GAZE, 2)),
-                149 => Ok(OpcodeMeta::new(raw, Opcode::TECH_DEMO_GESUTRE, 2)),
-                150 => Ok(OpcodeMeta::new(raw, Opcode::VR_CHEMICAL_LIGHT_COLOR, 2)),
-                151 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_MOB, 5)),
-                152 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_HAIR_OSAGE, 9)),
-                153 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_LOOK_CAMERA, 9)),
-                154 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_CHEER, 5)),
-                155 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_GESTURE, 3)),
-                156 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_CLONE, 7)),
-                157 => Ok(OpcodeMeta::new(raw, Opcode::VR_LOOP_EFFECT, 7)),
-                158 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_ONESHOT_EFFECT, 6)),
-                159 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_PRESENT, 9)),
-                160 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_TRANSFORM, 5)),
-                161 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_FLY, 5)),
-                162 => Ok(OpcodeMeta::new(raw, Opcode::VR_LIVE_CHARA_VOICE, 2)),
-                _ => Err(ApplicationError::UnknownOpcode(raw)),
-            },
-            Game::FutureTone => match raw {
-                0 => Ok(OpcodeMeta::new(raw, Opcode::END, 0)),
-                1 => Ok(OpcodeMeta::new(raw, Opcode::TIME, 1)),
-                2 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_MOVE, 4)),
-                3 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_ROT, 2)),
-                4 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_DISP, 2)),
-                5 => Ok(OpcodeMeta::new(raw, Opcode::MIKU_SHADOW, 2)),
-                6 => Ok(OpcodeMeta::new(raw, Opcode::TARGET, 7)),
-                7 => Ok(OpcodeMeta::new(raw, Opcode::SET_MOTION, 4)),
-                8 => Ok(OpcodeMeta::new(raw, Opcode::SET_PLAYDATA, 2)),
-                9 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT, 6)),
-                10 => Ok(OpcodeMeta::new(raw, Opcode::FADEIN_FIELD, 2)),
-                11 => Ok(OpcodeMeta::new(raw, Opcode::EFFECT_OFF, 1)),
-                12 => Ok(OpcodeMeta::new(raw, Opcode::SET_CAMERA, 6)),
-                13 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA, 2)),
-                14 => Ok(OpcodeMeta::new(raw, Opcode::CHANGE_FIELD, 1)),
-                15 => Ok(OpcodeMeta::new(raw, Opcode::HIDE_FIELD, 1)),
-                16 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_FIELD, 3)),
-                17 => Ok(OpcodeMeta::new(raw, Opcode::FADEOUT_FIELD, 2)),
-                18 => Ok(OpcodeMeta::new(raw, Opcode::EYE_ANIM, 3)),
-                19 => Ok(OpcodeMeta::new(raw, Opcode::MOUTH_ANIM, 5)),
-                20 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ANIM, 5)),
-                21 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_ANIM, 4)),
-                22 => Ok(OpcodeMeta::new(raw, Opcode::EXPRESSION, 4)),
-                23 => Ok(OpcodeMeta::new(raw, Opcode::LOOK_CAMERA, 5)),
-                24 => Ok(OpcodeMeta::new(raw, Opcode::LYRIC, 2)),
-                25 => Ok(OpcodeMeta::new(raw, Opcode::MUSIC_PLAY, 0)),
-                26 => Ok(OpcodeMeta::new(raw, Opcode::MODE_SELECT, 2)),
-                27 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION, 4)),
-                28 => Ok(OpcodeMeta::new(raw, Opcode::BAR_TIME_SET, 2)),
-                29 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWHEIGHT, 2)),
-                30 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_FACE, 1)),
-                31 => Ok(OpcodeMeta::new(raw, Opcode::MOVE_CAMERA, 21)),
-                32 => Ok(OpcodeMeta::new(raw, Opcode::PV_END, 0)),
-                33 => Ok(OpcodeMeta::new(raw, Opcode::SHADOWPOS, 3)),
-                34 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_LYRIC, 2)),
-                35 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_TARGET, 5)),
-                36 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH, 1)),
-                37 => Ok(OpcodeMeta::new(raw, Opcode::SET_CHARA, 1)),
-                38 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE, 7)),
-                39 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_SHADOW, 1)),
-                40 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID, 1)),
-                41 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE, 2)),
-                42 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_ITEM, 1)),
-                43 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EFFECT, 2)),
-                44 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_DISP, 1)),
-                45 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_HAND_ANIM, 2)),
-                46 => Ok(OpcodeMeta::new(raw, Opcode::AIM, 3)),
-                47 => Ok(OpcodeMeta::new(raw, Opcode::HAND_ITEM, 3)),
-                48 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_BLUSH, 1)),
-                49 => Ok(OpcodeMeta::new(raw, Opcode::NEAR_CLIP, 2)),
-                50 => Ok(OpcodeMeta::new(raw, Opcode::CLOTH_WET, 2)),
-                51 => Ok(OpcodeMeta::new(raw, Opcode::LIGHT_ROT, 3)),
-                52 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_FADE, 6)),
-                53 => Ok(OpcodeMeta::new(raw, Opcode::TONE_TRANS, 6)),
-                54 => Ok(OpcodeMeta::new(raw, Opcode::SATURATE, 1)),
-                55 => Ok(OpcodeMeta::new(raw, Opcode::FADE_MODE, 1)),
-                56 => Ok(OpcodeMeta::new(raw, Opcode::AUTO_BLINK, 2)),
-                57 => Ok(OpcodeMeta::new(raw, Opcode::PARTS_DISP, 3)),
-                58 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_FLYING_TIME, 1)),
-                59 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_SIZE, 2)),
-                60 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_HEIGHT_ADJUST, 2)),
-                61 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ANIM, 4)),
-                62 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_POS_ADJUST, 4)),
-                63 => Ok(OpcodeMeta::new(raw, Opcode::SCENE_ROT, 1)),
-                64 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOT_SMOOTH_LEN, 2)),
-                65 => Ok(OpcodeMeta::new(raw, Opcode::PV_BRANCH_MODE, 1)),
-                66 => Ok(OpcodeMeta::new(raw, Opcode::DATA_CAMERA_START, 2)),
-                67 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_PLAY, 1)),
-                68 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_DISP, 1)),
-                69 => Ok(OpcodeMeta::new(raw, Opcode::WIND, 3)),
-                70 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_STEP, 3)),
-                71 => Ok(OpcodeMeta::new(raw, Opcode::OSAGE_MV_CCL, 3)),
-                72 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_COLOR, 2)),
-                73 => Ok(OpcodeMeta::new(raw, Opcode::SE_EFFECT, 1)),
-                74 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOVE_XYZ, 9)),
-                75 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYELID_ANIM, 3)),
-                76 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_INSTRUMENT_ITEM, 2)),
-                77 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION_LOOP, 4)),
-                78 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EXPRESSION, 2)),
-                79 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_EYE_ANIM, 3)),
-                80 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOUTH_ANIM, 2)),
-                81 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_CAMERA, 24)),
-                82 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MODE_SELECT, 1)),
-                83 => Ok(OpcodeMeta::new(raw, Opcode::PV_END_FADEOUT, 2)),
-                84 => Ok(OpcodeMeta::new(raw, Opcode::TARGET_FLAG, 1)),
-                85 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ANIM_ATTACH, 3)),
-                86 => Ok(OpcodeMeta::new(raw, Opcode::SHADOW_RANGE, 1)),
-                87 => Ok(OpcodeMeta::new(raw, Opcode::HAND_SCALE, 3)),
-                88 => Ok(OpcodeMeta::new(raw, Opcode::LIGHT_POS, 4)),
-                89 => Ok(OpcodeMeta::new(raw, Opcode::FACE_TYPE, 1)),
-                90 => Ok(OpcodeMeta::new(raw, Opcode::SHADOW_CAST, 2)),
-                91 => Ok(OpcodeMeta::new(raw, Opcode::EDIT_MOTION_F, 6)),
-                92 => Ok(OpcodeMeta::new(raw, Opcode::FOG, 3)),
-                93 => Ok(OpcodeMeta::new(raw, Opcode::BLOOM, 2)),
-                94 => Ok(OpcodeMeta::new(raw, Opcode::COLOR_COLLE, 3)),
-                95 => Ok(OpcodeMeta::new(raw, Opcode::DOF, 3)),
-                96 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_ALPHA, 4)),
-                97 => Ok(OpcodeMeta::new(raw, Opcode::AOTO_CAP, 1)),
-                98 => Ok(OpcodeMeta::new(raw, Opcode::MAN_CAP, 1)),
-                99 => Ok(OpcodeMeta::new(raw, Opcode::TOON, 3)),
-                100 => Ok(OpcodeMeta::new(raw, Opcode::SHIMMER, 3)),
-                101 => Ok(OpcodeMeta::new(raw, Opcode::ITEM_ALPHA, 4)),
-                102 => Ok(OpcodeMeta::new(raw, Opcode::MOVIE_CUT_CHG, 1)),
-                103 => Ok(OpcodeMeta::new(raw, Opcode::CHARA_LIGHT, 3)),
-                104 => Ok(OpcodeMeta::new(raw, Opcode::STAGE_LIGHT, 3)),
-                105 => Ok(OpcodeMeta::new(raw, Opcode::AGEAGE_CTRL, 8)),
-                106 => Ok(OpcodeMeta::new(raw, Opcode::PSE, 2)),
-                _ => Err(ApplicationError::UnknownOpcode(raw)),
-            },
-            _ => Err(ApplicationError::UnsupportedGame(game)),
-        }
+impl std::cmp::PartialEq for Command {
+    fn eq(&self, other: &Self) -> bool {
+        self.meta.opcode == other.meta.opcode && self.args == other.args
     }
+}
 
-    pub fn get_opcode_meta_from_name(game: Game, name: String) -> ApplicationResult<OpcodeMeta> {
-        let to = match game {
-            Game::F => 83,
-            Game::F2nd => 110,
-            Game::X => 162,
-            Game::FutureTone => 106,
-            _ => 0,
-        };
+/// Held by tests that load custom opcode definitions, as those are shared
+/// by every test.
+#[cfg(test)]
+pub(crate) static CUSTOM_DEFINITIONS_LOCK: Mutex<()> = Mutex::new(());
 
-        for i in 0..to {
-            let meta = Command::get_opcode_meta(game, i);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            match meta {
-                Ok(m) => {
-                    if format!("{:?}", m.opcode) == name {
-                        return Ok(m);
-                    }
-                }
-                Err(_) => {}
-            }
-        }
+    fn load(source: &str) -> ApplicationResult {
+        load_opcode_definitions(&mut source.as_bytes())
+    }
 
-        Err(ApplicationError::UnknownOpcodeName(name))
+    fn name_of(game: Game, id: i32) -> Option<&'static str> {
+        Command::get_opcode_meta(game, id)
+            .ok()
+            .map(|meta| meta.opcode.name())
     }
-}
 
-impl std::cmp::PartialEq for Command {
-    fn eq(&self, other: &Self) -> bool {
-        self.meta.opcode == other.meta.opcode && self.args == other.args
+    #[test]
+    fn custom_definitions() {
+        let _lock = CUSTOM_DEFINITIONS_LOCK.lock().unwrap();
+        clear_opcode_definitions();
+
+        load("[f]\n900 TEST_OPCODE 2\n6 TEST_TARGET 3\n").unwrap();
+
+        assert_eq!(name_of(Game::F, 900), Some("TEST_OPCODE"));
+        assert_eq!(name_of(Game::F, 6), Some("TEST_TARGET"));
+        assert!(Command::get_opcode_meta_from_opcode(Game::F, Opcode::TARGET).is_none());
+
+        // A mistake anywhere in a file keeps all of it from being loaded.
+        assert!(load("[f]\n901 OTHER_OPCODE 1\nnot a definition\n").is_err());
+        assert_eq!(name_of(Game::F, 901), None);
+        assert_eq!(name_of(Game::F, 900), Some("TEST_OPCODE"));
+
+        clear_opcode_definitions();
+
+        assert_eq!(name_of(Game::F, 900), None);
+        assert_eq!(name_of(Game::F, 6), Some("TARGET"));
+        assert!(Command::get_opcode_meta_from_opcode(Game::F, Opcode::TARGET).is_some());
     }
 }
@@ -0,0 +1,1131 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use crate::common::{format_timestamp, Game};
+use crate::opcodes::{Command, Opcode, OpcodeMeta, ParamKind, TargetDefaults};
+
+/// A problem found while reading a plaintext script, pointing at the place
+/// in the source where it occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+    pub message: String,
+    pub source_line: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+
+        writeln!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+
+        let gutter = self.line.to_string().len();
+
+        // Columns count characters, so tabs in front of the caret are kept
+        // as tabs to line it up with the source line above.
+        let padding = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(
+            f,
+            "{} | {}{}",
+            " ".repeat(gutter),
+            padding,
+            "^".repeat(self.length.max(1))
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Identifier(String),
+    Number(i32),
+    Time(TimeLiteral),
+    LeftParen,
+    RightParen,
+    Comma,
+    Semicolon,
+    Equals,
+    /// `#include "file"`, with the file name as written.
+    Include(String),
+    /// `#define NAME value`, where the value is a number or a time.
+    Define(String, Box<TokenKind>),
+    End,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Identifier(name) => write!(f, "`{}`", name),
+            TokenKind::Number(number) => write!(f, "`{}`", number),
+            TokenKind::Time(_) => write!(f, "a time"),
+            TokenKind::LeftParen => write!(f, "`(`"),
+            TokenKind::RightParen => write!(f, "`)`"),
+            TokenKind::Comma => write!(f, "`,`"),
+            TokenKind::Semicolon => write!(f, "`;`"),
+            TokenKind::Equals => write!(f, "`=`"),
+            TokenKind::Include(_) => write!(f, "an `#include`"),
+            TokenKind::Define(_, _) => write!(f, "a `#define`"),
+            TokenKind::End => write!(f, "end of file"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+    length: usize,
+}
+
+/// A time written as `MM:SS.mmm`, in seconds (`83.456s`) or in milliseconds
+/// (`500ms`). Times with a leading `+` or `-` are relative to the time of the
+/// last `TIME` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeLiteral {
+    ticks: i64,
+    relative: bool,
+}
+
+/// Why text couldn't be read as a [`TimeLiteral`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeError {
+    Invalid,
+    /// `MM:SS.mmm` with 60 or more seconds, or minutes after hours.
+    OutOfRange,
+}
+
+impl TimeLiteral {
+    fn parse(text: &str) -> Option<Self> {
+        Self::try_parse(text).ok()
+    }
+
+    fn try_parse(text: &str) -> Result<Self, TimeError> {
+        let (relative, negative, body) = match text.strip_prefix('+') {
+            Some(body) => (true, false, body),
+            None => match text.strip_prefix('-') {
+                Some(body) => (true, true, body),
+                None => (false, false, text),
+            },
+        };
+
+        let ticks = if let Some(millis) = body.strip_suffix("ms") {
+            parse_decimal(millis, 100).ok_or(TimeError::Invalid)?
+        } else if let Some(seconds) = body.strip_suffix('s') {
+            parse_decimal(seconds, 100000).ok_or(TimeError::Invalid)?
+        } else if body.contains(':') {
+            let components = body.split(':').collect::<Vec<&str>>();
+
+            if components.len() > 3 {
+                return Err(TimeError::Invalid);
+            }
+
+            let (seconds, rest) = components.split_last().ok_or(TimeError::Invalid)?;
+            let mut ticks = parse_decimal(seconds, 100000).ok_or(TimeError::Invalid)?;
+            let mut unit = 100000 * 60;
+
+            if ticks >= unit {
+                return Err(TimeError::OutOfRange);
+            }
+
+            for (idx, component) in rest.iter().rev().enumerate() {
+                if component.is_empty() || !component.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(TimeError::Invalid);
+                }
+
+                let value = component.parse::<i64>().map_err(|_| TimeError::Invalid)?;
+
+                // The leading component may be as large as it likes.
+                if idx + 1 < rest.len() && value >= 60 {
+                    return Err(TimeError::OutOfRange);
+                }
+
+                ticks += value * unit;
+                unit *= 60;
+            }
+
+            ticks
+        } else {
+            return Err(TimeError::Invalid);
+        };
+
+        Ok(Self {
+            ticks: if negative { -ticks } else { ticks },
+            relative,
+        })
+    }
+}
+
+/// The message for text that is neither a number nor a time.
+fn invalid_time_message(text: &str) -> String {
+    match TimeLiteral::try_parse(text) {
+        Err(TimeError::OutOfRange) => {
+            format!("seconds and minutes in `{}` have to be below 60", text)
+        }
+        _ => format!("invalid number or time `{}`", text),
+    }
+}
+
+/// Parses a point in time written in ticks or in the time notation, such as
+/// `01:10.000` or `70s`.
+pub fn parse_time(text: &str) -> Option<i32> {
+    if let Some(ticks) = parse_number(text) {
+        return Some(ticks);
+    }
+
+    match TimeLiteral::parse(text)? {
+        time if !time.relative => i32::try_from(time.ticks).ok(),
+        _ => None,
+    }
+}
+
+/// Parses a time range written as `START-END`, e.g. `00:30.000-00:34.000`.
+/// The end has to come after the start.
+pub fn parse_time_range(text: &str) -> Option<(i32, i32)> {
+    let (start, end) = text.split_once('-')?;
+    let start = parse_time(start.trim())?;
+    let end = parse_time(end.trim())?;
+
+    if start < 0 || end <= start {
+        return None;
+    }
+
+    Some((start, end))
+}
+
+/// Parses a time offset such as `+250ms`, `-1.5s` or `00:02.000` into ticks.
+/// Offsets without a sign are positive.
+pub fn parse_offset(text: &str) -> Option<i32> {
+    let time = if text.starts_with('+') || text.starts_with('-') {
+        TimeLiteral::parse(text)?
+    } else {
+        TimeLiteral::parse(&format!("+{}", text))?
+    };
+
+    i32::try_from(time.ticks).ok()
+}
+
+/// Parses an unsigned decimal like `83.456` into a multiple of `unit`, the
+/// number of ticks the whole part stands for. Fails if the fraction is more
+/// precise than a tick.
+fn parse_decimal(text: &str, unit: i64) -> Option<i64> {
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+
+    if whole.is_empty() || !(whole.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut value = whole.parse::<i64>().ok()?.checked_mul(unit)?;
+    let mut scale = unit;
+
+    for digit in fraction.chars() {
+        if scale % 10 != 0 {
+            return None;
+        }
+
+        scale /= 10;
+        value += digit.to_digit(10)? as i64 * scale;
+    }
+
+    Some(value)
+}
+
+/// Formats a time in the `MM:SS.mmm` notation accepted by the parser, with
+/// extra digits if the time isn't a whole number of milliseconds. Negative
+/// times are written as plain numbers, since a leading `-` would make them
+/// relative.
+pub fn format_time_literal(ticks: i32) -> String {
+    if ticks < 0 {
+        return ticks.to_string();
+    }
+
+    if ticks % 100 == 0 {
+        return format_timestamp(ticks);
+    }
+
+    format!(
+        "{:02}:{:02}.{:05}",
+        ticks / 6000000,
+        ticks / 100000 % 60,
+        ticks % 100000
+    )
+}
+
+/// How times are written when dumping a script to plaintext.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimeFormat {
+    /// Raw ticks, e.g. `TIME(8345600);`.
+    #[default]
+    Ticks,
+    /// Raw ticks followed by a comment, e.g. `TIME(8345600); # 01:23.456`.
+    Comment,
+    /// The time notation accepted by the parser, e.g. `TIME(01:23.456);`.
+    Inline,
+}
+
+impl TimeFormat {
+    pub fn from_string(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "ticks" | "raw" => Some(TimeFormat::Ticks),
+            "comment" | "comments" => Some(TimeFormat::Comment),
+            "inline" => Some(TimeFormat::Inline),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DumpOptions {
+    pub time_format: TimeFormat,
+    /// Names each argument (`TARGET(type=0, x=480, ...)`) and adds the time
+    /// of each command as a comment.
+    pub annotate: bool,
+    /// Indents the commands that follow a `TIME` under it.
+    pub group_by_time: bool,
+}
+
+/// Writes a command as a line of plaintext that the parser can read back.
+pub fn format_command(command: &Command, options: &DumpOptions) -> String {
+    format_line(command, options, None)
+}
+
+fn format_line(command: &Command, options: &DumpOptions, event_time: Option<i32>) -> String {
+    let meta = &command.meta;
+    let kind = |index: usize| meta.params.get(index).map(|param| param.kind);
+
+    let args = command
+        .args
+        .iter()
+        .enumerate()
+        .map(|(idx, arg)| {
+            let value = match (options.time_format, kind(idx)) {
+                (TimeFormat::Inline, Some(ParamKind::Time)) => format_time_literal(*arg),
+                (TimeFormat::Inline, Some(ParamKind::Duration)) if *arg >= 0 => {
+                    format!("{}ms", arg)
+                }
+                _ => arg.to_string(),
+            };
+
+            if options.annotate {
+                format!("{}={}", meta.param_name(idx), value)
+            } else {
+                value
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let mut line = format!("{}({});", meta.opcode, args);
+    let mut times = event_time.into_iter().collect::<Vec<i32>>();
+
+    if options.time_format == TimeFormat::Comment {
+        times.extend(
+            command
+                .args
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| kind(*idx) == Some(ParamKind::Time))
+                .map(|(_, arg)| *arg),
+        );
+    }
+
+    times.dedup();
+
+    if !times.is_empty() {
+        let times = times
+            .into_iter()
+            .map(format_timestamp)
+            .collect::<Vec<String>>();
+
+        line.push_str(&format!(" # {}", times.join(", ")));
+    }
+
+    line
+}
+
+/// Writes a whole script, one command per line.
+pub fn format_script(commands: &[Command], options: &DumpOptions) -> String {
+    let mut output = String::new();
+    let mut time = 0;
+    let mut in_group = false;
+
+    for command in commands {
+        let is_time = command.meta.opcode == Opcode::TIME;
+
+        if is_time {
+            if let Some(&new_time) = command.args.first() {
+                time = new_time;
+            }
+        }
+
+        if options.group_by_time {
+            if is_time {
+                if !output.is_empty() {
+                    output.push('\n');
+                }
+
+                in_group = true;
+            } else if in_group {
+                output.push_str("    ");
+            }
+        }
+
+        // Grouped commands share the time shown on their TIME header, which
+        // needs no comment if the time is already written out.
+        let show_time = if is_time {
+            options.time_format != TimeFormat::Inline
+        } else {
+            !in_group
+        };
+
+        let event_time = (options.annotate && show_time).then_some(time);
+
+        output.push_str(&format_line(command, options, event_time));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Splits a plaintext script into tokens. Comments start with `#` or `//`
+/// and run to the end of the line. Numbers may be negative and written in
+/// hexadecimal (`0x1F`).
+struct Lexer<'a> {
+    source: &'a str,
+    lines: Vec<&'a str>,
+    file: Option<String>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            lines: source.lines().collect(),
+            file: None,
+        }
+    }
+
+    fn diagnostic(&self, line: usize, column: usize, length: usize, message: String) -> Diagnostic {
+        Diagnostic {
+            file: self.file.clone(),
+            line,
+            column,
+            length,
+            message,
+            source_line: self.lines.get(line - 1).unwrap_or(&"").to_string(),
+        }
+    }
+
+    fn tokenize(&self) -> Result<Vec<Token>, Diagnostic> {
+        let mut tokens = Vec::new();
+
+        for (line_idx, line) in self.source.lines().enumerate() {
+            if let Some(token) = self.directive(line_idx + 1, line)? {
+                tokens.push(token);
+                continue;
+            }
+
+            let chars = line.chars().collect::<Vec<char>>();
+            let mut pos = 0;
+
+            while pos < chars.len() {
+                let c = chars[pos];
+                let start = pos;
+
+                let kind = match c {
+                    c if c.is_whitespace() => {
+                        pos += 1;
+                        continue;
+                    }
+                    '#' => break,
+                    '/' if chars.get(pos + 1) == Some(&'/') => break,
+                    '(' => TokenKind::LeftParen,
+                    ')' => TokenKind::RightParen,
+                    ',' => TokenKind::Comma,
+                    ';' => TokenKind::Semicolon,
+                    '=' => TokenKind::Equals,
+                    c if c.is_alphabetic() || c == '_' || c == '＿' => {
+                        while pos + 1 < chars.len()
+                            && (chars[pos + 1].is_alphanumeric()
+                                || chars[pos + 1] == '_'
+                                || chars[pos + 1] == '＿')
+                        {
+                            pos += 1;
+                        }
+
+                        TokenKind::Identifier(chars[start..=pos].iter().collect())
+                    }
+                    c if c.is_ascii_digit() || c == '-' || c == '+' => {
+                        while pos + 1 < chars.len()
+                            && (chars[pos + 1].is_alphanumeric()
+                                || chars[pos + 1] == ':'
+                                || chars[pos + 1] == '.')
+                        {
+                            pos += 1;
+                        }
+
+                        let text = chars[start..=pos].iter().collect::<String>();
+
+                        match parse_number(&text) {
+                            Some(number) => TokenKind::Number(number),
+                            None => match TimeLiteral::parse(&text) {
+                                Some(time) => TokenKind::Time(time),
+                                None => {
+                                    return Err(self.diagnostic(
+                                        line_idx + 1,
+                                        start + 1,
+                                        text.chars().count(),
+                                        invalid_time_message(&text),
+                                    ));
+                                }
+                            },
+                        }
+                    }
+                    c => {
+                        return Err(self.diagnostic(
+                            line_idx + 1,
+                            start + 1,
+                            1,
+                            format!("unexpected character `{}`", c),
+                        ));
+                    }
+                };
+
+                pos += 1;
+
+                tokens.push(Token {
+                    kind,
+                    line: line_idx + 1,
+                    column: start + 1,
+                    length: pos - start,
+                });
+            }
+        }
+
+        let line = self.lines.len().max(1);
+
+        tokens.push(Token {
+            kind: TokenKind::End,
+            line,
+            column: self.lines.last().map_or(0, |line| line.chars().count()) + 1,
+            length: 1,
+        });
+
+        Ok(tokens)
+    }
+
+    /// Reads an `#include "file"` or `#define NAME value` directive, which
+    /// takes up a line of its own. Any other line starting with `#` is a
+    /// comment.
+    fn directive(&self, line_number: usize, line: &str) -> Result<Option<Token>, Diagnostic> {
+        let trimmed = line.trim();
+        let column = line.chars().count() - line.trim_start().chars().count() + 1;
+        let length = trimmed.chars().count();
+        let error = |message: String| self.diagnostic(line_number, column, length, message);
+
+        let (directive, rest) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let rest = rest.trim_start();
+
+        let kind = match directive {
+            "#include" => {
+                let quoted = rest
+                    .strip_prefix('"')
+                    .and_then(|rest| rest.split_once('"'))
+                    .filter(|(path, rest)| !path.is_empty() && is_blank_or_comment(rest));
+
+                match quoted {
+                    Some((path, _)) => TokenKind::Include(path.to_string()),
+                    None => {
+                        return Err(error(
+                            "expected a quoted file name after `#include`".to_string(),
+                        ));
+                    }
+                }
+            }
+            "#define" => {
+                let mut words = rest.splitn(3, char::is_whitespace);
+
+                let (name, value) = match (words.next(), words.next()) {
+                    (Some(name), Some(value))
+                        if is_blank_or_comment(words.next().unwrap_or("")) =>
+                    {
+                        (name, value)
+                    }
+                    _ => {
+                        return Err(error(
+                            "expected a name and a value after `#define`".to_string(),
+                        ));
+                    }
+                };
+
+                if !is_identifier(name) {
+                    return Err(error(format!("invalid constant name `{}`", name)));
+                }
+
+                let value = match parse_number(value) {
+                    Some(number) => TokenKind::Number(number),
+                    None => match TimeLiteral::parse(value) {
+                        Some(time) => TokenKind::Time(time),
+                        None => {
+                            return Err(error(invalid_time_message(value)));
+                        }
+                    },
+                };
+
+                TokenKind::Define(name.to_string(), Box::new(value))
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Token {
+            kind,
+            line: line_number,
+            column,
+            length,
+        }))
+    }
+}
+
+fn is_blank_or_comment(text: &str) -> bool {
+    let text = text.trim_start();
+    text.is_empty() || text.starts_with('#') || text.starts_with("//")
+}
+
+fn is_identifier(text: &str) -> bool {
+    let is_part = |c: char| c.is_alphanumeric() || c == '_' || c == '＿';
+
+    text.chars()
+        .next()
+        .is_some_and(|c| !c.is_numeric() && is_part(c))
+        && text.chars().all(is_part)
+}
+
+fn parse_number(text: &str) -> Option<i32> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    let hex = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"));
+
+    let value = match hex {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i64>().ok()?,
+    };
+
+    // Hexadecimal values may use all 32 bits, e.g. 0xFFFFFFFF for -1.
+    if hex.is_some() && !negative && value <= u32::MAX as i64 {
+        return Some(value as u32 as i32);
+    }
+
+    i32::try_from(if negative { -value } else { value }).ok()
+}
+
+/// Parses plaintext scripts in the format used by Open PD Script Editor, i.e.
+/// one `NAME(arg, arg, ...);` call per command.
+///
+/// Scripts may also pull in other scripts with `#include "file"` and name
+/// values with `#define NAME value`, which can then be used as arguments.
+pub struct PlaintextParser<'a> {
+    game: Game,
+    pad_missing_args: bool,
+    current_time: i32,
+    target_defaults: TargetDefaults,
+    path: Option<PathBuf>,
+    defines: HashMap<String, TokenKind>,
+    // Files that are being parsed, from the outermost one, to catch files
+    // that (indirectly) include themselves.
+    includes: Vec<PathBuf>,
+    lexer: Lexer<'a>,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl<'a> PlaintextParser<'a> {
+    pub fn new(game: Game, source: &'a str) -> Self {
+        Self {
+            game,
+            pad_missing_args: false,
+            current_time: 0,
+            target_defaults: TargetDefaults::default(),
+            path: None,
+            defines: HashMap::new(),
+            includes: Vec::new(),
+            lexer: Lexer::new(source),
+            tokens: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Fills in missing trailing arguments instead of rejecting commands that
+    /// have fewer arguments than their opcode takes. `TARGET` parameters get
+    /// their [`TargetDefaults`] where they have one, everything else 0.
+    pub fn pad_missing_args(mut self, pad_missing_args: bool) -> Self {
+        self.pad_missing_args = pad_missing_args;
+        self
+    }
+
+    /// Sets the file the script was read from. Included files are looked up
+    /// relative to it, and diagnostics name it. Without a path, includes are
+    /// relative to the working directory.
+    pub fn path(mut self, path: &Path) -> Self {
+        self.lexer.file = Some(path.display().to_string());
+        self.includes
+            .push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    fn error_at(&self, token: &Token, message: String) -> Diagnostic {
+        self.lexer
+            .diagnostic(token.line, token.column, token.length, message)
+    }
+
+    fn expect(&mut self, kind: TokenKind, description: &str) -> Result<Token, Diagnostic> {
+        let token = self.next();
+
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(self.error_at(
+                &token,
+                format!("expected {}, found {}", description, token.kind),
+            ))
+        }
+    }
+
+    /// Converts a time literal to the unit of the parameter it is used for:
+    /// ticks for points in time, milliseconds for durations.
+    fn resolve_time(
+        &self,
+        meta: &OpcodeMeta,
+        index: usize,
+        time: TimeLiteral,
+        token: &Token,
+    ) -> Result<i32, Diagnostic> {
+        let kind = meta.params.get(index).map(|param| param.kind);
+
+        let value = match kind {
+            Some(ParamKind::Time) if time.relative => self.current_time as i64 + time.ticks,
+            Some(ParamKind::Time) => time.ticks,
+            Some(ParamKind::Duration) if !time.relative && time.ticks % 100 == 0 => {
+                time.ticks / 100
+            }
+            Some(ParamKind::Duration) if !time.relative => {
+                return Err(self.error_at(
+                    token,
+                    "durations must be a whole number of milliseconds".to_string(),
+                ));
+            }
+            _ if time.relative => {
+                return Err(self.error_at(
+                    token,
+                    format!(
+                        "relative times can only be used for points in time, not `{}` of {}",
+                        meta.param_name(index),
+                        meta.opcode
+                    ),
+                ));
+            }
+            _ => {
+                return Err(self.error_at(
+                    token,
+                    format!(
+                        "`{}` of {} is not a time or a duration",
+                        meta.param_name(index),
+                        meta.opcode
+                    ),
+                ));
+            }
+        };
+
+        match i32::try_from(value) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(self.error_at(token, "time is out of range".to_string())),
+        }
+    }
+
+    fn parse_command(&mut self) -> Result<Command, Diagnostic> {
+        let name_token = self.next();
+
+        let name = match &name_token.kind {
+            TokenKind::Identifier(name) => name.clone(),
+            kind => {
+                return Err(self.error_at(
+                    &name_token,
+                    format!("expected a command name, found {}", kind),
+                ));
+            }
+        };
+
+        let meta = match Command::get_opcode_meta_from_name(self.game, name.clone()) {
+            Ok(meta) => meta,
+            Err(_) => {
+                return Err(self.error_at(
+                    &name_token,
+                    format!("unknown opcode `{}` for Project Diva {}", name, self.game),
+                ));
+            }
+        };
+
+        self.expect(TokenKind::LeftParen, "`(`")?;
+
+        let mut args = Vec::new();
+
+        if self.peek().kind == TokenKind::RightParen {
+            self.next();
+        } else {
+            loop {
+                let mut token = self.next();
+
+                // Arguments may be named like in annotated dumps, in which
+                // case the name has to match the parameter.
+                if let TokenKind::Identifier(param_name) = &token.kind {
+                    if self.peek().kind == TokenKind::Equals {
+                        let expected = meta.param_name(args.len());
+
+                        if *param_name != expected {
+                            return Err(self.error_at(
+                                &token,
+                                format!(
+                                    "expected argument `{}` of {}, found `{}`",
+                                    expected, meta.opcode, param_name
+                                ),
+                            ));
+                        }
+
+                        self.next();
+                        token = self.next();
+                    }
+                }
+
+                match &token.kind {
+                    TokenKind::Number(number) => args.push(*number),
+                    TokenKind::Time(time) => {
+                        let value = self.resolve_time(&meta, args.len(), *time, &token)?;
+                        args.push(value);
+                    }
+                    TokenKind::Identifier(name) => match self.defines.get(name) {
+                        Some(TokenKind::Number(number)) => args.push(*number),
+                        Some(TokenKind::Time(time)) => {
+                            let value = self.resolve_time(&meta, args.len(), *time, &token)?;
+                            args.push(value);
+                        }
+                        _ => {
+                            return Err(
+                                self.error_at(&token, format!("unknown constant `{}`", name))
+                            );
+                        }
+                    },
+                    kind => {
+                        return Err(
+                            self.error_at(&token, format!("expected a number, found {}", kind))
+                        );
+                    }
+                }
+
+                let token = self.next();
+
+                match &token.kind {
+                    TokenKind::Comma => continue,
+                    TokenKind::RightParen => break,
+                    kind => {
+                        return Err(
+                            self.error_at(&token, format!("expected `,` or `)`, found {}", kind))
+                        );
+                    }
+                }
+            }
+        }
+
+        if self.peek().kind == TokenKind::Semicolon {
+            self.next();
+        }
+
+        if self.pad_missing_args && args.len() < meta.param_count {
+            let defaults = self.target_defaults;
+
+            let padding = (args.len()..meta.param_count).map(|idx| {
+                meta.params
+                    .get(idx)
+                    .filter(|_| meta.opcode == Opcode::TARGET)
+                    .and_then(|param| defaults.get(param.name))
+                    .unwrap_or(0)
+            });
+
+            args.extend(padding.collect::<Vec<i32>>());
+        }
+
+        if args.len() != meta.param_count {
+            return Err(self.error_at(
+                &name_token,
+                format!(
+                    "{} takes {} argument(s), but {} were given",
+                    name,
+                    meta.param_count,
+                    args.len()
+                ),
+            ));
+        }
+
+        Ok(Command::new(meta, args))
+    }
+
+    /// Parses the file named by an `#include` in place of the directive.
+    /// Constants it defines stay defined for the rest of the including file.
+    fn include(
+        &mut self,
+        token: &Token,
+        filename: &str,
+        commands: &mut Vec<Command>,
+    ) -> Result<(), Diagnostic> {
+        let path = match &self.path {
+            Some(including) => including
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(filename),
+            None => PathBuf::from(filename),
+        };
+
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                return Err(
+                    self.error_at(token, format!("cannot include `{}`: {}", path.display(), e))
+                );
+            }
+        };
+
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+
+        if self.includes.contains(&canonical) {
+            return Err(self.error_at(token, format!("`{}` includes itself", path.display())));
+        }
+
+        let mut parser =
+            PlaintextParser::new(self.game, &source).pad_missing_args(self.pad_missing_args);
+        parser.includes = self.includes.clone();
+
+        let mut parser = parser.path(&path);
+        parser.defines = std::mem::take(&mut self.defines);
+        parser.current_time = self.current_time;
+        parser.target_defaults = self.target_defaults;
+
+        let result = parser.parse_into(commands);
+
+        self.defines = parser.defines;
+        self.current_time = parser.current_time;
+        self.target_defaults = parser.target_defaults;
+
+        result
+    }
+
+    fn parse_into(&mut self, commands: &mut Vec<Command>) -> Result<(), Diagnostic> {
+        self.tokens = self.lexer.tokenize()?;
+        self.position = 0;
+
+        loop {
+            let token = self.peek().clone();
+
+            match &token.kind {
+                TokenKind::End => break,
+                TokenKind::Semicolon => {
+                    self.next();
+                }
+                TokenKind::Include(filename) => {
+                    self.next();
+                    self.include(&token, filename, commands)?;
+                }
+                TokenKind::Define(name, value) => {
+                    self.next();
+                    self.defines.insert(name.clone(), *value.clone());
+                }
+                _ => {
+                    let command = self.parse_command()?;
+                    self.target_defaults.track(&command);
+
+                    if command.meta.opcode == Opcode::TIME {
+                        if let Some(&time) = command.args.first() {
+                            self.current_time = time;
+                        }
+                    }
+
+                    commands.push(command);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn parse(mut self) -> Result<Vec<Command>, Diagnostic> {
+        let mut commands = Vec::new();
+        self.parse_into(&mut commands)?;
+
+        Ok(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Vec<Command>, Diagnostic> {
+        PlaintextParser::new(Game::FutureTone, source).parse()
+    }
+
+    fn args(source: &str) -> Vec<Vec<i32>> {
+        parse(source)
+            .unwrap()
+            .into_iter()
+            .map(|command| command.args)
+            .collect()
+    }
+
+    #[test]
+    fn hex_literals() {
+        assert_eq!(args("TIME(0x1F);"), vec![vec![31]]);
+        assert_eq!(args("TIME(0xFFFFFFFF);"), vec![vec![-1]]);
+    }
+
+    #[test]
+    fn trailing_comments() {
+        assert_eq!(
+            args("TIME(100); // one\nTIME(200); # two\n"),
+            vec![vec![100], vec![200]]
+        );
+    }
+
+    #[test]
+    fn time_literals() {
+        assert_eq!(args("TIME(01:23.456);"), vec![vec![8345600]]);
+        assert_eq!(args("TIME(83.456s);"), vec![vec![8345600]]);
+        assert_eq!(
+            args("TIME(83.456s);\nTIME(+500ms);"),
+            vec![vec![8345600], vec![8395600]]
+        );
+    }
+
+    #[test]
+    fn pads_target_with_defaults() {
+        let source = "
+            TIME(0);
+            TARGET_FLYING_TIME(1200);
+            BAR_TIME_SET(120, 3);
+            TARGET(0, 500);
+            TARGET(1, 500, 1000, 480000, 240000);
+            MUSIC_PLAY();
+        ";
+
+        let commands = PlaintextParser::new(Game::F2nd, source)
+            .pad_missing_args(true)
+            .parse()
+            .unwrap();
+
+        let args = commands
+            .iter()
+            .filter(|command| command.meta.opcode == Opcode::TARGET)
+            .map(|command| command.args.clone())
+            .collect::<Vec<Vec<i32>>>();
+
+        assert_eq!(
+            args,
+            vec![
+                vec![0, 500, -1, 0, 0, 0, 0, 0, 0, 1200, 3, 0],
+                vec![1, 500, 1000, 480000, 240000, 0, 0, 0, 0, 1200, 3, 0],
+            ]
+        );
+    }
+
+    #[test]
+    fn pads_with_zeros_without_defaults() {
+        let commands = PlaintextParser::new(Game::F2nd, "TARGET(0);")
+            .pad_missing_args(true)
+            .parse()
+            .unwrap();
+
+        assert_eq!(commands[0].args, vec![0, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn seconds_have_to_be_below_60() {
+        assert_eq!(args("TIME(01:59.999);"), vec![vec![11999900]]);
+        assert_eq!(args("TIME(1:00:00.000);"), vec![vec![360000000]]);
+        assert_eq!(args("TIME(90:00.000);"), vec![vec![540000000]]);
+
+        let diagnostic = parse("TIME(01:99.000);").unwrap_err();
+        assert_eq!((diagnostic.line, diagnostic.column), (1, 6));
+        assert_eq!(
+            diagnostic.message,
+            "seconds and minutes in `01:99.000` have to be below 60"
+        );
+
+        assert!(parse("TIME(1:75:00.000);").is_err());
+        assert_eq!(parse_time("01:60.000"), None);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert!(parse("TIME(100").is_err());
+        assert!(parse("TIME(100;").is_err());
+        assert!(parse("TIME(12ab);").is_err());
+        assert!(parse("TIME(100) $").is_err());
+    }
+
+    #[test]
+    fn diagnostic_points_at_the_error() {
+        let diagnostic = parse("TIME(100);\nTIME(12ab);").unwrap_err();
+
+        assert_eq!((diagnostic.line, diagnostic.column), (2, 6));
+        assert_eq!(diagnostic.length, 4);
+        assert_eq!(
+            diagnostic.to_string().lines().collect::<Vec<&str>>(),
+            vec![
+                "2:6: invalid number or time `12ab`",
+                "2 | TIME(12ab);",
+                "  |      ^^^^",
+            ]
+        );
+    }
+
+    #[test]
+    fn diagnostic_caret_follows_tabs() {
+        let diagnostic = parse("\t\tTIME(12ab);").unwrap_err();
+
+        assert_eq!(diagnostic.column, 8);
+        assert_eq!(
+            diagnostic.to_string().lines().last(),
+            Some("  | \t\t     ^^^^")
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt::{Display, Formatter};
+
+use crate::common::format_timestamp;
+use crate::plaintext::{parse_offset, parse_time, parse_time_range};
+
+/// A change to the length of a song, such as a shortened intro or an
+/// extended bridge, that the commands have to follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetimeEdit {
+    /// Inserts `duration` ticks of silence at `at`, moving everything from
+    /// that point on later.
+    Insert { at: i32, duration: i32 },
+    /// Cuts out the time from `start` up to (but not including) `end`,
+    /// dropping the commands in it and moving everything after it earlier.
+    Remove { start: i32, end: i32 },
+}
+
+impl RetimeEdit {
+    /// Parses `DURATION@TIME`, e.g. `2.5s@01:10.000`.
+    pub fn parse_insert(text: &str) -> Option<Self> {
+        let (duration, at) = text.split_once('@')?;
+        let duration = parse_offset(duration.trim())?;
+        let at = parse_time(at.trim())?;
+
+        if duration <= 0 || at < 0 {
+            return None;
+        }
+
+        Some(RetimeEdit::Insert { at, duration })
+    }
+
+    /// Parses `START-END`, e.g. `00:30.000-00:34.000`.
+    pub fn parse_remove(text: &str) -> Option<Self> {
+        let (start, end) = parse_time_range(text)?;
+        Some(RetimeEdit::Remove { start, end })
+    }
+
+    /// The point in time from which on the edit changes anything.
+    pub fn position(&self) -> i32 {
+        match *self {
+            RetimeEdit::Insert { at, .. } => at,
+            RetimeEdit::Remove { start, .. } => start,
+        }
+    }
+
+    /// Returns where a command at `time` ends up, or `None` if the edit
+    /// removes it.
+    pub fn apply(&self, time: i32) -> Option<i32> {
+        match *self {
+            RetimeEdit::Insert { at, duration } if time >= at => {
+                Some(time.saturating_add(duration))
+            }
+            RetimeEdit::Remove { start, end } if time >= end => Some(time - (end - start)),
+            RetimeEdit::Remove { start, .. } if time >= start => None,
+            _ => Some(time),
+        }
+    }
+}
+
+impl Display for RetimeEdit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            RetimeEdit::Insert { at, duration } => write!(
+                f,
+                "insert {} at {}",
+                format_timestamp(duration),
+                format_timestamp(at)
+            ),
+            RetimeEdit::Remove { start, end } => write!(
+                f,
+                "remove {}-{}",
+                format_timestamp(start),
+                format_timestamp(end)
+            ),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs::File, io::Read};
+use std::io::Read;
 
 use subparse::{SrtFile, SsaFile, SubtitleEntry, SubtitleFileInterface};
 
@@ -7,6 +7,7 @@ use crate::error::{ApplicationError, ApplicationResult};
 use crate::logger::Logger;
 use crate::opcodes::Command;
 
+#[allow(clippy::upper_case_acronyms)]
 pub enum SubtitleKind {
     SRT,
     ASS,
@@ -22,6 +23,23 @@ impl SubtitleKind {
             _ => None,
         }
     }
+
+    /// Tells the formats apart by their contents, for files that don't have
+    /// an extension (like standard input). ASS and SSA files start with a
+    /// `[Script Info]` section.
+    pub fn from_contents(contents: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(contents);
+
+        if text
+            .trim_start_matches('\u{feff}')
+            .trim_start()
+            .starts_with('[')
+        {
+            SubtitleKind::ASS
+        } else {
+            SubtitleKind::SRT
+        }
+    }
 }
 
 pub struct SubtitleFile<'a> {
@@ -30,9 +48,12 @@ pub struct SubtitleFile<'a> {
 }
 
 impl<'a> SubtitleFile<'a> {
-    pub fn load_srt(file: &mut File, logger: &'a mut dyn Logger) -> ApplicationResult<Self> {
+    pub fn load_srt<R: Read>(
+        reader: &mut R,
+        logger: &'a mut dyn Logger,
+    ) -> ApplicationResult<Self> {
         let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+        reader.read_to_end(&mut buffer)?;
 
         let file_contents = std::str::from_utf8(&buffer)?;
 
@@ -43,13 +64,16 @@ impl<'a> SubtitleFile<'a> {
                 let entries = srt.get_subtitle_entries().unwrap_or(Vec::new());
                 Ok(Self { entries, logger })
             }
-            Err(_) => return Err(ApplicationError::InvalidSubtitleFile),
+            Err(_) => Err(ApplicationError::InvalidSubtitleFile),
         }
     }
 
-    pub fn load_ass(file: &mut File, logger: &'a mut dyn Logger) -> ApplicationResult<Self> {
+    pub fn load_ass<R: Read>(
+        reader: &mut R,
+        logger: &'a mut dyn Logger,
+    ) -> ApplicationResult<Self> {
         let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+        reader.read_to_end(&mut buffer)?;
 
         let file_contents = std::str::from_utf8(&buffer)?;
 
@@ -60,7 +84,7 @@ impl<'a> SubtitleFile<'a> {
                 let entries = ass.get_subtitle_entries().unwrap_or(Vec::new());
                 Ok(Self { entries, logger })
             }
-            Err(_) => return Err(ApplicationError::InvalidSubtitleFile),
+            Err(_) => Err(ApplicationError::InvalidSubtitleFile),
         }
     }
 
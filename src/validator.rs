@@ -0,0 +1,92 @@
+use std::fmt::{Display, Formatter};
+
+use crate::dsc::DSCVM;
+use crate::opcodes::Opcode;
+
+/// A problem found in a script. `index` is the position of the offending
+/// command in the command buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub index: usize,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn new(index: usize, message: String) -> Self {
+        Self { index, message }
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "command #{}: {}", self.index, self.message)
+    }
+}
+
+/// Checks a script for problems that would make the game misbehave:
+/// commands with the wrong number of arguments, time going backwards, and a
+/// missing or misplaced `END`.
+pub fn validate(dsc_vm: &DSCVM) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut current_ts: Option<i32> = None;
+    let mut end_index: Option<usize> = None;
+
+    for (index, command) in dsc_vm.command_buffer.iter().enumerate() {
+        let meta = command.meta;
+
+        if command.args.len() != meta.param_count {
+            issues.push(ValidationIssue::new(
+                index,
+                format!(
+                    "{} takes {} argument(s), but has {}",
+                    meta.opcode,
+                    meta.param_count,
+                    command.args.len()
+                ),
+            ));
+        }
+
+        if meta.opcode == Opcode::END {
+            end_index.get_or_insert(index);
+        }
+
+        if meta.opcode != Opcode::TIME {
+            continue;
+        }
+
+        if let Some(&time) = command.args.first() {
+            if time < 0 {
+                issues.push(ValidationIssue::new(
+                    index,
+                    format!("TIME({}) is negative", time),
+                ));
+            }
+
+            match current_ts {
+                Some(previous) if time < previous => issues.push(ValidationIssue::new(
+                    index,
+                    format!("TIME({}) goes back from TIME({})", time, previous),
+                )),
+                _ => {}
+            }
+
+            current_ts = Some(time);
+        }
+    }
+
+    let last_index = dsc_vm.command_buffer.len().saturating_sub(1);
+
+    match end_index {
+        None => issues.push(ValidationIssue::new(
+            dsc_vm.command_buffer.len(),
+            "the script does not end with END".to_string(),
+        )),
+        Some(index) if index != last_index => issues.push(ValidationIssue::new(
+            index,
+            "END is followed by more commands, which will be ignored".to_string(),
+        )),
+        _ => {}
+    }
+
+    issues
+}